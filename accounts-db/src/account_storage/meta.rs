@@ -160,6 +160,12 @@ impl<'storage> StoredAccountMeta<'storage> {
         }
     }
 
+    /// Returns the write version persisted with this account.
+    ///
+    /// Only append vecs persist write versions, as part of StoredMeta.
+    /// Tiered accounts files drop them on write, so accounts read from
+    /// them always return the default write version, and the order of
+    /// stores within a file has to be used to resolve the latest account.
     pub fn write_version(&self) -> StoredMetaWriteVersion {
         match self {
             Self::AppendVec(av) => av.write_version(),
@@ -6,35 +6,54 @@ pub mod file;
 pub mod footer;
 pub mod hot;
 pub mod index;
+pub mod lookup_cache;
 pub mod meta;
 pub mod mmap_utils;
 pub mod owners;
 pub mod readable;
+pub mod recovery;
+#[cfg(test)]
+pub(crate) mod test_utils;
 pub mod writer;
 
 use {
     crate::{
-        account_storage::meta::{StorableAccountsWithHashesAndWriteVersions, StoredAccountInfo},
+        account_storage::meta::{
+            StorableAccountsWithHashesAndWriteVersions, StoredAccountInfo, StoredAccountMeta,
+        },
         accounts_hash::AccountHash,
         storable_accounts::StorableAccounts,
     },
     error::TieredStorageError,
-    footer::{AccountBlockFormat, AccountMetaFormat},
-    hot::{HotStorageWriter, HOT_FORMAT},
-    index::IndexBlockFormat,
+    footer::{AccountBlockFormat, AccountMetaFormat, TieredStorageFooter},
+    hot::{HotAccountMeta, HotStorageWriter, HOT_FORMAT},
+    index::{IndexBlockFormat, IndexOffset},
     owners::OwnersBlockFormat,
     readable::TieredStorageReader,
-    solana_sdk::account::ReadableAccount,
+    recovery::RecoveryOutcome,
+    solana_sdk::{account::ReadableAccount, hash::Hash, pubkey::Pubkey},
     std::{
         borrow::Borrow,
-        fs::{self, OpenOptions},
+        fs::{self, File, OpenOptions},
         path::{Path, PathBuf},
-        sync::OnceLock,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            OnceLock,
+        },
     },
 };
 
 pub type TieredStorageResult<T> = Result<T, TieredStorageError>;
 
+/// The total number of bytes memory-mapped by all the live readers.
+static TOTAL_MAPPED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the total number of bytes memory-mapped by all the live
+/// TieredStorageReaders, which helps tune how many of them are kept open.
+pub fn total_mapped_bytes() -> u64 {
+    TOTAL_MAPPED_BYTES.load(Ordering::Relaxed)
+}
+
 /// The struct that defines the formats of all building blocks of a
 /// TieredStorage.
 #[derive(Clone, Debug, PartialEq)]
@@ -46,14 +65,124 @@ pub struct TieredStorageFormat {
     pub account_block_format: AccountBlockFormat,
 }
 
+impl TieredStorageFormat {
+    /// Returns Ok(()) if the fields of this format are consistent with each
+    /// other.  TieredStorageError::InvalidMetaEntrySize will be returned if
+    /// meta_entry_size doesn't match the size of the entry serialized by
+    /// account_meta_format, and TieredStorageError::IncompatibleBlockFormat
+    /// if account_meta_format cannot be used with account_block_format.
+    pub fn validate(&self) -> TieredStorageResult<()> {
+        let expected_meta_entry_size = meta_entry_size(self.account_meta_format);
+        if self.meta_entry_size != expected_meta_entry_size {
+            return Err(TieredStorageError::InvalidMetaEntrySize(
+                self.meta_entry_size,
+                expected_meta_entry_size,
+            ));
+        }
+
+        match (self.account_meta_format, self.account_block_format) {
+            // hot accounts are accessed in place, so they are never compressed
+            (AccountMetaFormat::Hot, AccountBlockFormat::AlignedRaw) => Ok(()),
+            (account_meta_format, account_block_format) => {
+                Err(TieredStorageError::IncompatibleBlockFormat(
+                    account_meta_format,
+                    account_block_format,
+                ))
+            }
+        }
+    }
+}
+
+/// Returns the size of the account meta entry serialized by the specified
+/// AccountMetaFormat.
+fn meta_entry_size(account_meta_format: AccountMetaFormat) -> usize {
+    match account_meta_format {
+        AccountMetaFormat::Hot => std::mem::size_of::<HotAccountMeta>(),
+    }
+}
+
+/// A builder of TieredStorageFormat, which derives meta_entry_size from the
+/// chosen AccountMetaFormat and validates the combination of formats.
+///
+/// Each format starts with its default value, so that a default builder
+/// produces HOT_FORMAT.
+#[derive(Clone, Debug, Default)]
+pub struct TieredStorageFormatBuilder {
+    account_meta_format: AccountMetaFormat,
+    owners_block_format: OwnersBlockFormat,
+    index_block_format: IndexBlockFormat,
+    account_block_format: AccountBlockFormat,
+}
+
+impl TieredStorageFormatBuilder {
+    pub fn account_meta_format(mut self, account_meta_format: AccountMetaFormat) -> Self {
+        self.account_meta_format = account_meta_format;
+        self
+    }
+
+    pub fn owners_block_format(mut self, owners_block_format: OwnersBlockFormat) -> Self {
+        self.owners_block_format = owners_block_format;
+        self
+    }
+
+    pub fn index_block_format(mut self, index_block_format: IndexBlockFormat) -> Self {
+        self.index_block_format = index_block_format;
+        self
+    }
+
+    pub fn account_block_format(mut self, account_block_format: AccountBlockFormat) -> Self {
+        self.account_block_format = account_block_format;
+        self
+    }
+
+    /// Returns the TieredStorageFormat of the chosen formats, or the error
+    /// returned by TieredStorageFormat::validate() if they are incompatible.
+    pub fn build(self) -> TieredStorageResult<TieredStorageFormat> {
+        let format = TieredStorageFormat {
+            meta_entry_size: meta_entry_size(self.account_meta_format),
+            account_meta_format: self.account_meta_format,
+            owners_block_format: self.owners_block_format,
+            index_block_format: self.index_block_format,
+            account_block_format: self.account_block_format,
+        };
+        format.validate()?;
+        Ok(format)
+    }
+}
+
+/// When a writable TieredStorage syncs its file to disk, which trades the
+/// durability of freshly written files for write throughput.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FsyncPolicy {
+    /// The file is synced by write_accounts() before it is opened for read.
+    #[default]
+    Always,
+    /// The file is never synced implicitly and is left to the OS to write
+    /// back, unless sync() is called.
+    Never,
+    /// The file is synced by seal_into_readonly() instead of by
+    /// write_accounts().
+    OnSeal,
+}
+
 #[derive(Debug)]
 pub struct TieredStorage {
     reader: OnceLock<TieredStorageReader>,
+    /// Set by the first write_accounts() call, which guarantees that only
+    /// one of several concurrent callers gets to write the file.  It is
+    /// cleared again if that write fails.
+    write_started: AtomicBool,
     path: PathBuf,
+    /// Whether the backing file is removed when this instance is dropped.
+    remove_on_drop: bool,
+    fsync_policy: FsyncPolicy,
 }
 
 impl Drop for TieredStorage {
     fn drop(&mut self) {
+        if !self.remove_on_drop {
+            return;
+        }
         if let Err(err) = fs::remove_file(&self.path) {
             panic!(
                 "TieredStorage failed to remove backing storage file '{}': {err}",
@@ -72,8 +201,41 @@ impl TieredStorage {
     pub fn new_writable(path: impl Into<PathBuf>) -> Self {
         Self {
             reader: OnceLock::<TieredStorageReader>::new(),
+            write_started: AtomicBool::new(false),
             path: path.into(),
+            remove_on_drop: true,
+            fsync_policy: FsyncPolicy::default(),
+        }
+    }
+
+    /// Same as new_writable(), but checks up front that the parent directory
+    /// of `path` exists and is writable, instead of deferring any failure
+    /// to write_accounts().
+    ///
+    /// TieredStorageError::ParentDirMissing is returned if the parent
+    /// directory does not exist, and an I/O error of kind PermissionDenied
+    /// if it is read-only.
+    pub fn new_writable_checked(path: impl Into<PathBuf>) -> TieredStorageResult<Self> {
+        let path = path.into();
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            // a relative path with a single component lives in the current dir
+            _ => Path::new("."),
+        };
+        match fs::metadata(parent) {
+            Ok(metadata) if metadata.is_dir() => {
+                if metadata.permissions().readonly() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::PermissionDenied,
+                        format!("directory {} is read-only", parent.display()),
+                    )
+                    .into());
+                }
+            }
+            _ => return Err(TieredStorageError::ParentDirMissing(parent.to_path_buf())),
         }
+
+        Ok(Self::new_writable(path))
     }
 
     /// Creates a new read-only instance of TieredStorage from the
@@ -82,10 +244,75 @@ impl TieredStorage {
         let path = path.into();
         Ok(Self {
             reader: TieredStorageReader::new_from_path(&path).map(OnceLock::from)?,
+            write_started: AtomicBool::new(true),
             path,
+            remove_on_drop: true,
+            fsync_policy: FsyncPolicy::default(),
+        })
+    }
+
+    /// Same as new_readonly(), but the file is never removed when the
+    /// returned instance is dropped, so several instances can safely share
+    /// the same path.
+    pub fn new_readonly_keep(path: impl Into<PathBuf>) -> TieredStorageResult<Self> {
+        let mut tiered_storage = Self::new_readonly(path)?;
+        tiered_storage.remove_on_drop = false;
+        Ok(tiered_storage)
+    }
+
+    /// Creates a new read-only instance of TieredStorage from an
+    /// already-opened file.
+    ///
+    /// As the caller owns the file, it will not be removed when the
+    /// returned instance is dropped.  The returned instance has no path,
+    /// so path-based operations such as sync() and copy_to() will fail.
+    pub fn new_readonly_from_file(file: File) -> TieredStorageResult<Self> {
+        Ok(Self {
+            reader: TieredStorageReader::new_from_file(file).map(OnceLock::from)?,
+            write_started: AtomicBool::new(true),
+            path: PathBuf::new(),
+            remove_on_drop: false,
+            fsync_policy: FsyncPolicy::default(),
         })
     }
 
+    /// Sets the FsyncPolicy that decides when the file written by this
+    /// instance is synced to disk.  FsyncPolicy::Always is used by default.
+    pub fn with_fsync_policy(mut self, fsync_policy: FsyncPolicy) -> Self {
+        self.fsync_policy = fsync_policy;
+        self
+    }
+
+    /// Consumes this instance, whose accounts have been written, and returns
+    /// a fresh read-only instance over the same file, which now owns it.
+    /// The file is synced first under FsyncPolicy::OnSeal.
+    ///
+    /// On success, the file is not removed when this instance is dropped.
+    /// On error, this instance is dropped as usual.
+    pub fn seal_into_readonly(mut self) -> TieredStorageResult<TieredStorage> {
+        if self.fsync_policy == FsyncPolicy::OnSeal {
+            self.sync()?;
+        }
+        let readonly = TieredStorage::new_readonly(&self.path)?;
+        self.remove_on_drop = false;
+        Ok(readonly)
+    }
+
+    /// Makes the hot accounts file at `path` readable again if its footer is
+    /// missing or torn, e.g. after a crash in write_accounts(), by rebuilding
+    /// the footer from the account, index, and owners blocks that precede
+    /// it.  The slot is only recovered if it survived in the torn footer.
+    ///
+    /// The file is only replaced once the recovered file has been validated,
+    /// and the original is kept next to it as `<path>.corrupt`, since the
+    /// layout of the blocks is inferred and may be wrong.
+    /// RecoveryOutcome::AlreadyValid is returned, without changing the file,
+    /// if its footer is valid, and TieredStorageError::Unrecoverable if no
+    /// intact blocks could be found.
+    pub fn recover(path: impl AsRef<Path>) -> TieredStorageResult<RecoveryOutcome> {
+        recovery::recover(path.as_ref())
+    }
+
     /// Returns the path to this TieredStorage.
     pub fn path(&self) -> &Path {
         self.path.as_path()
@@ -96,7 +323,10 @@ impl TieredStorage {
     /// Note that this function can only be called once per a TieredStorage
     /// instance.  TieredStorageError::AttemptToUpdateReadOnly will be returned
     /// if this function is invoked more than once on the same TieredStorage
-    /// instance.
+    /// instance, including when the calls race from different threads.
+    ///
+    /// A failed write leaves no file behind and does not count as the one
+    /// call, so it can be retried.
     pub fn write_accounts<
         'a,
         'b,
@@ -109,37 +339,182 @@ impl TieredStorage {
         skip: usize,
         format: &TieredStorageFormat,
     ) -> TieredStorageResult<Vec<StoredAccountInfo>> {
+        self.write_accounts_with_footer(accounts, skip, format)
+            .map(|(stored_infos, _footer)| stored_infos)
+    }
+
+    /// Same as write_accounts(), but also returns the footer that has been
+    /// persisted, which saves reaching through the reader for it.
+    pub fn write_accounts_with_footer<
+        'a,
+        'b,
+        T: ReadableAccount + Sync,
+        U: StorableAccounts<'a, T>,
+        V: Borrow<AccountHash>,
+    >(
+        &self,
+        accounts: &StorableAccountsWithHashesAndWriteVersions<'a, 'b, T, U, V>,
+        skip: usize,
+        format: &TieredStorageFormat,
+    ) -> TieredStorageResult<(Vec<StoredAccountInfo>, TieredStorageFooter)> {
         if self.is_read_only() {
             return Err(TieredStorageError::AttemptToUpdateReadOnly(
                 self.path.to_path_buf(),
             ));
         }
 
+        format.validate()?;
+
         if format == &HOT_FORMAT {
-            let result = {
-                let writer = HotStorageWriter::new(&self.path)?;
-                writer.write_accounts(accounts, skip)
-            };
+            // Only the first caller proceeds, as the others could otherwise
+            // pass the `is_read_only()` check before the reader is set.
+            if self.write_started.swap(true, Ordering::AcqRel) {
+                return Err(TieredStorageError::AttemptToUpdateReadOnly(
+                    self.path.to_path_buf(),
+                ));
+            }
 
-            // panic here if self.reader.get() is not None as self.reader can only be
-            // None since we have passed `is_read_only()` check previously, indicating
-            // self.reader is not yet set.
-            self.reader
-                .set(TieredStorageReader::new_from_path(&self.path)?)
-                .unwrap();
+            let writer = match HotStorageWriter::with_capacity(
+                &self.path,
+                accounts.accounts.len().saturating_sub(skip),
+            ) {
+                Ok(writer) => writer,
+                Err(err) => {
+                    // nothing has been written, so the write can be retried
+                    self.write_started.store(false, Ordering::Release);
+                    return Err(err);
+                }
+            };
+            let result = writer
+                .write_accounts_with_footer(accounts, skip)
+                .and_then(|result| {
+                    if self.fsync_policy == FsyncPolicy::Always {
+                        // make the file durable before it is opened by the reader.
+                        writer.flush_and_sync()?;
+                    }
+                    Ok(result)
+                });
+            drop(writer);
+            let result = result.and_then(|result| {
+                let reader = TieredStorageReader::new_from_path(&self.path)?;
+                Ok((result, reader))
+            });
 
-            return result;
+            return match result {
+                Ok((result, reader)) => {
+                    // panic here if self.reader.get() is not None as self.reader can
+                    // only be None since we are the only caller that has claimed
+                    // `write_started`, indicating self.reader is not yet set.
+                    self.reader.set(reader).unwrap();
+                    Ok(result)
+                }
+                Err(err) => {
+                    // The partial file is removed so that the write can be
+                    // retried from scratch.
+                    let _ = fs::remove_file(&self.path);
+                    self.write_started.store(false, Ordering::Release);
+                    Err(err)
+                }
+            };
         }
 
         Err(TieredStorageError::UnknownFormat(self.path.to_path_buf()))
     }
 
+    /// Flushes the data and metadata of the underlying accounts file to disk,
+    /// regardless of the TieredStorageFormat it was written with.
+    ///
+    /// An error will be returned if the file has not been created yet.
+    pub fn sync(&self) -> TieredStorageResult<()> {
+        let file = OpenOptions::new().read(true).open(&self.path)?;
+        Ok(file.sync_all()?)
+    }
+
     /// Returns the underlying reader of the TieredStorage.  None will be
     /// returned if it's is_read_only() returns false.
     pub fn reader(&self) -> Option<&TieredStorageReader> {
         self.reader.get()
     }
 
+    /// Copies the underlying accounts file to `dest` byte by byte, and
+    /// returns a new read-only TieredStorage instance backed by the copy.
+    ///
+    /// The copy is synced to disk and validated as a tiered storage file
+    /// before it is returned.  The returned instance owns the copy, which
+    /// will be removed when it is dropped, independently of `self`.
+    pub fn copy_to(&self, dest: impl Into<PathBuf>) -> TieredStorageResult<TieredStorage> {
+        let dest = dest.into();
+        let result = fs::copy(&self.path, &dest)
+            .and_then(|_| OpenOptions::new().read(true).open(&dest))
+            .and_then(|file| file.sync_all())
+            .map_err(TieredStorageError::from)
+            .and_then(|_| TieredStorage::new_readonly(&dest));
+        if result.is_err() {
+            // a partial copy is not owned by any instance yet
+            let _ = fs::remove_file(&dest);
+        }
+        result
+    }
+
+    /// Writes the accounts of all the `inputs` into a new file at `dest`
+    /// with the specified format, and returns a read-only TieredStorage
+    /// instance that owns it.
+    ///
+    /// An address held by several inputs resolves to its account in the
+    /// last of them, following the order of `inputs`.  The merged file is
+    /// written for the largest slot among the inputs.
+    pub fn merge(
+        inputs: &[&TieredStorageReader],
+        dest: impl Into<PathBuf>,
+        format: &TieredStorageFormat,
+    ) -> TieredStorageResult<TieredStorage> {
+        let dest = dest.into();
+        format.validate()?;
+        if format != &HOT_FORMAT {
+            return Err(TieredStorageError::UnknownFormat(dest));
+        }
+
+        let accounts = inputs
+            .iter()
+            .map(|input| input.accounts(IndexOffset(0)))
+            .collect::<TieredStorageResult<Vec<_>>>()?;
+        let num_accounts = accounts.iter().map(Vec::len).sum();
+        let slot = inputs
+            .iter()
+            .map(|input| input.slot())
+            .max()
+            .unwrap_or_default();
+
+        let result = HotStorageWriter::with_capacity(&dest, num_accounts)
+            .and_then(|writer| {
+                let writer = writer.with_duplicates_collapsed();
+                writer.write_accounts_iter(
+                    accounts.iter().flatten().map(|account| {
+                        (
+                            account.pubkey(),
+                            account,
+                            account.hash(),
+                            account.write_version(),
+                        )
+                    }),
+                    slot,
+                )?;
+                writer.flush_and_sync()
+            })
+            .and_then(|_| TieredStorage::new_readonly(&dest));
+        if result.is_err() {
+            // a partially merged file is not owned by any instance yet
+            let _ = fs::remove_file(&dest);
+        }
+        result
+    }
+
+    /// Returns a read-only view of the footer of the underlying accounts file.
+    /// None will be returned if it's is_read_only() returns false.
+    pub fn footer(&self) -> Option<&TieredStorageFooter> {
+        self.reader().map(TieredStorageReader::footer)
+    }
+
     /// Returns true if the TieredStorage instance is read-only.
     pub fn is_read_only(&self) -> bool {
         self.reader.get().is_some()
@@ -156,18 +531,65 @@ impl TieredStorage {
     }
 }
 
+/// Returns true if the two tiered storage files hold the same logical
+/// accounts, i.e. the same set of (pubkey, lamports, data, owner, hash)
+/// tuples, regardless of the order in which the accounts are indexed and
+/// of how the index and owners blocks are laid out.
+///
+/// A logical mismatch returns Ok(false); errors are reserved for failures
+/// in reading the accounts of either file.
+pub fn files_equivalent(
+    a: &TieredStorageReader,
+    b: &TieredStorageReader,
+) -> TieredStorageResult<bool> {
+    if a.num_accounts() != b.num_accounts() {
+        return Ok(false);
+    }
+
+    let a_accounts = a.accounts(IndexOffset(0))?;
+    let b_accounts = b.accounts(IndexOffset(0))?;
+    Ok(sorted_account_tuples(&a_accounts) == sorted_account_tuples(&b_accounts))
+}
+
+/// Returns the (pubkey, lamports, data, owner, hash) tuples of `accounts`
+/// in sorted order.
+fn sorted_account_tuples<'a>(
+    accounts: &'a [StoredAccountMeta],
+) -> Vec<(&'a Pubkey, u64, &'a [u8], &'a Pubkey, &'a Hash)> {
+    let mut tuples: Vec<_> = accounts
+        .iter()
+        .map(|account| {
+            (
+                account.pubkey(),
+                account.lamports(),
+                account.data(),
+                account.owner(),
+                &account.hash().0,
+            )
+        })
+        .collect();
+    tuples.sort_unstable();
+    tuples
+}
+
 #[cfg(test)]
 mod tests {
     use {
         super::*,
-        crate::account_storage::meta::{StoredAccountMeta, StoredMeta, StoredMetaWriteVersion},
-        footer::{TieredStorageFooter, TieredStorageMagicNumber},
+        crate::{
+            account_storage::meta::{StoredMeta, StoredMetaWriteVersion},
+            accounts_db::AccountsDb,
+            accounts_file::MatchAccountOwnerError,
+        },
+        assert_matches::assert_matches,
+        file::TieredStorageFile,
+        footer::{TieredStorageMagicNumber, FOOTER_SIZE},
         hot::HOT_FORMAT,
-        index::IndexOffset,
         owners::OWNER_NO_OWNER,
+        readable::HashState,
         solana_accounts_db::rent_collector::RENT_EXEMPT_RENT_EPOCH,
         solana_sdk::{
-            account::{Account, AccountSharedData},
+            account::{Account, AccountSharedData, WritableAccount},
             clock::Slot,
             hash::Hash,
             pubkey::Pubkey,
@@ -175,17 +597,15 @@ mod tests {
         },
         std::{
             collections::{HashMap, HashSet},
+            io::{Seek, SeekFrom, Write},
             mem::ManuallyDrop,
+            sync::{Arc, Barrier},
+            thread,
         },
-        tempfile::tempdir,
+        tempfile::{tempdir, TempDir},
+        test_utils::{new_account_hashes, with_storable_accounts, write_hot_accounts},
     };
 
-    impl TieredStorage {
-        fn footer(&self) -> Option<&TieredStorageFooter> {
-            self.reader.get().map(|r| r.footer())
-        }
-    }
-
     /// Simply invoke write_accounts with empty vector to allow the tiered storage
     /// to persist non-account blocks such as footer, index block, etc.
     fn write_zero_accounts(
@@ -275,6 +695,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_write_accounts_retry_after_failure() {
+        let temp_dir = tempdir().unwrap();
+        let tiered_storage_path = temp_dir.path().join("test_write_accounts_retry");
+        let tiered_storage = TieredStorage::new_writable(&tiered_storage_path);
+        let account = create_account(1);
+        let account_refs = [(&account.0.pubkey, &account.1); 2];
+        let account_data = (Slot::MAX, &account_refs[..]);
+        let storable_accounts =
+            StorableAccountsWithHashesAndWriteVersions::new_with_hashes_and_write_versions(
+                &account_data,
+                vec![AccountHash(Hash::new_unique()); 2],
+                vec![0; 2],
+            );
+
+        // the file cannot be created while another one is in the way
+        fs::write(&tiered_storage_path, []).unwrap();
+        assert_matches!(
+            tiered_storage.write_accounts(&storable_accounts, 0, &HOT_FORMAT),
+            Err(TieredStorageError::Io(_))
+        );
+        assert!(!tiered_storage.is_read_only());
+        fs::remove_file(&tiered_storage_path).unwrap();
+
+        // a write that fails midway leaves no partial file behind
+        assert_matches!(
+            tiered_storage.write_accounts(&storable_accounts, 0, &HOT_FORMAT),
+            Err(TieredStorageError::DuplicatePubkey(address)) if address == account.0.pubkey
+        );
+        assert!(!tiered_storage.is_read_only());
+        assert!(!tiered_storage_path.try_exists().unwrap());
+
+        write_zero_accounts(&tiered_storage, Ok(vec![]));
+        assert!(tiered_storage.is_read_only());
+    }
+
+    #[test]
+    fn test_write_accounts_concurrently() {
+        let temp_dir = tempdir().unwrap();
+        let tiered_storage_path = temp_dir.path().join("test_write_accounts_concurrently");
+        let tiered_storage = Arc::new(TieredStorage::new_writable(&tiered_storage_path));
+        let barrier = Arc::new(Barrier::new(2));
+
+        let writers: Vec<_> = (0..2)
+            .map(|_| {
+                let tiered_storage = Arc::clone(&tiered_storage);
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    let account_refs = Vec::<(&Pubkey, &AccountSharedData)>::new();
+                    let account_data = (Slot::MAX, account_refs.as_slice());
+                    let storable_accounts =
+                        StorableAccountsWithHashesAndWriteVersions::new_with_hashes_and_write_versions(
+                            &account_data,
+                            Vec::<AccountHash>::new(),
+                            Vec::<StoredMetaWriteVersion>::new(),
+                        );
+                    barrier.wait();
+                    tiered_storage.write_accounts(&storable_accounts, 0, &HOT_FORMAT)
+                })
+            })
+            .collect();
+        let results: Vec<_> = writers
+            .into_iter()
+            .map(|writer| writer.join().unwrap())
+            .collect();
+
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+        assert_eq!(
+            results
+                .iter()
+                .filter(|result| matches!(
+                    result,
+                    Err(TieredStorageError::AttemptToUpdateReadOnly(path))
+                        if *path == tiered_storage_path
+                ))
+                .count(),
+            1
+        );
+        assert!(tiered_storage.is_read_only());
+    }
+
     #[test]
     fn test_remove_on_drop() {
         // Generate a new temp path that is guaranteed to NOT already have a file.
@@ -310,6 +811,82 @@ mod tests {
         assert!(!tiered_storage_path.try_exists().unwrap());
     }
 
+    #[test]
+    fn test_validate_format() {
+        assert_matches!(HOT_FORMAT.validate(), Ok(()));
+
+        let format = TieredStorageFormat {
+            meta_entry_size: HOT_FORMAT.meta_entry_size + 1,
+            ..HOT_FORMAT.clone()
+        };
+        assert_matches!(
+            format.validate(),
+            Err(TieredStorageError::InvalidMetaEntrySize(size, expected_size))
+                if size == HOT_FORMAT.meta_entry_size + 1
+                    && expected_size == HOT_FORMAT.meta_entry_size
+        );
+
+        // Generate a new temp path that is guaranteed to NOT already have a file.
+        let temp_dir = tempdir().unwrap();
+        let tiered_storage_path = temp_dir.path().join("test_validate_format");
+        // ManuallyDrop as the file is never created when the format is rejected.
+        let tiered_storage = ManuallyDrop::new(TieredStorage::new_writable(&tiered_storage_path));
+        let account_refs = Vec::<(&Pubkey, &AccountSharedData)>::new();
+        let account_data = (Slot::MAX, account_refs.as_slice());
+        let storable_accounts =
+            StorableAccountsWithHashesAndWriteVersions::new_with_hashes_and_write_versions(
+                &account_data,
+                Vec::<AccountHash>::new(),
+                Vec::<StoredMetaWriteVersion>::new(),
+            );
+        assert_matches!(
+            tiered_storage.write_accounts(&storable_accounts, 0, &format),
+            Err(TieredStorageError::InvalidMetaEntrySize(_, _))
+        );
+        assert!(!tiered_storage.is_read_only());
+        assert!(!tiered_storage_path.try_exists().unwrap());
+    }
+
+    #[test]
+    fn test_format_builder() {
+        assert_eq!(
+            TieredStorageFormatBuilder::default().build().unwrap(),
+            HOT_FORMAT
+        );
+        assert_eq!(
+            TieredStorageFormatBuilder::default()
+                .account_meta_format(AccountMetaFormat::Hot)
+                .owners_block_format(OwnersBlockFormat::AddressesOnly)
+                .index_block_format(IndexBlockFormat::AddressesThenOffsets)
+                .account_block_format(AccountBlockFormat::AlignedRaw)
+                .build()
+                .unwrap(),
+            HOT_FORMAT
+        );
+    }
+
+    #[test]
+    fn test_format_builder_incompatible() {
+        assert_matches!(
+            TieredStorageFormatBuilder::default()
+                .account_block_format(AccountBlockFormat::Lz4)
+                .build(),
+            Err(TieredStorageError::IncompatibleBlockFormat(
+                AccountMetaFormat::Hot,
+                AccountBlockFormat::Lz4
+            ))
+        );
+
+        let format = TieredStorageFormat {
+            account_block_format: AccountBlockFormat::Lz4,
+            ..HOT_FORMAT.clone()
+        };
+        assert_matches!(
+            format.validate(),
+            Err(TieredStorageError::IncompatibleBlockFormat(_, _))
+        );
+    }
+
     /// Create a test account based on the specified seed.
     fn create_account(seed: u64) -> (StoredMeta, AccountSharedData) {
         let data_byte = seed as u8;
@@ -333,6 +910,67 @@ mod tests {
         (stored_meta, AccountSharedData::from(account))
     }
 
+    /// Writes the test accounts created from the specified data sizes into
+    /// a new TieredStorage under `temp_dir`.  Returns the TieredStorage
+    /// together with the written accounts and their account hashes.
+    fn write_test_accounts(
+        temp_dir: &TempDir,
+        path_suffix: &str,
+        account_data_sizes: &[u64],
+    ) -> (
+        TieredStorage,
+        Vec<(StoredMeta, AccountSharedData)>,
+        Vec<AccountHash>,
+    ) {
+        let accounts: Vec<_> = account_data_sizes
+            .iter()
+            .map(|size| create_account(*size))
+            .collect();
+        let (tiered_storage, hashes) = write_accounts_to(temp_dir, path_suffix, &accounts);
+        (tiered_storage, accounts, hashes)
+    }
+
+    /// Writes the specified accounts with unique account hashes into a new
+    /// TieredStorage under `temp_dir`.  Returns the TieredStorage together
+    /// with the account hashes.
+    fn write_accounts_to(
+        temp_dir: &TempDir,
+        path_suffix: &str,
+        accounts: &[(StoredMeta, AccountSharedData)],
+    ) -> (TieredStorage, Vec<AccountHash>) {
+        let hashes = new_account_hashes(accounts.len());
+        let tiered_storage = write_accounts_with_hashes(temp_dir, path_suffix, accounts, &hashes);
+        (tiered_storage, hashes)
+    }
+
+    /// Writes the specified accounts with the specified account hashes into
+    /// a new TieredStorage under `temp_dir`.
+    fn write_accounts_with_hashes(
+        temp_dir: &TempDir,
+        path_suffix: &str,
+        accounts: &[(StoredMeta, AccountSharedData)],
+        hashes: &[AccountHash],
+    ) -> TieredStorage {
+        let tiered_storage = TieredStorage::new_writable(temp_dir.path().join(path_suffix));
+        write_accounts_for_slot(&tiered_storage, Slot::MAX, accounts, hashes);
+        tiered_storage
+    }
+
+    /// Writes the specified accounts with the specified account hashes for
+    /// `slot` into `tiered_storage`.
+    fn write_accounts_for_slot(
+        tiered_storage: &TieredStorage,
+        slot: Slot,
+        accounts: &[(StoredMeta, AccountSharedData)],
+        hashes: &[AccountHash],
+    ) {
+        with_storable_accounts(slot, accounts, hashes, |storable_accounts| {
+            tiered_storage
+                .write_accounts(storable_accounts, 0, &HOT_FORMAT)
+                .unwrap();
+        });
+    }
+
     fn verify_account(
         stored_meta: &StoredAccountMeta<'_>,
         account: Option<&impl ReadableAccount>,
@@ -443,13 +1081,1517 @@ mod tests {
     }
 
     #[test]
-    fn test_write_accounts_mixed_size() {
+    fn test_write_accounts_zero_len() {
+        // An empty-data account with lamports still has optional fields in
+        // its account block, while a zero-lamport one has an empty block.
+        let empty_data_account = || {
+            let (mut stored_meta, mut account) = create_account(0);
+            account.set_lamports(10);
+            account.set_rent_epoch(RENT_EXEMPT_RENT_EPOCH);
+            stored_meta.data_len = 0;
+            (stored_meta, account)
+        };
+        let accounts = vec![
+            create_account(5),
+            empty_data_account(),
+            create_account(0),
+            create_account(0),
+            create_account(7),
+            empty_data_account(),
+        ];
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, hashes) =
+            write_accounts_to(&temp_dir, "test_write_accounts_zero_len", &accounts);
+        let reader = tiered_storage.reader().unwrap();
+
+        let mut index_offset = IndexOffset(0);
+        for (i, ((stored_meta, account), hash)) in accounts.iter().zip(&hashes).enumerate() {
+            let (stored_account, next) = reader.get_account(index_offset).unwrap().unwrap();
+            assert_eq!(next, IndexOffset(i as u32 + 1));
+            assert_eq!(stored_account.pubkey(), &stored_meta.pubkey);
+            assert_eq!(stored_account.lamports(), account.lamports());
+            assert_eq!(stored_account.data(), account.data());
+            if account.data().is_empty() {
+                assert_eq!(stored_account.data(), &[] as &[u8]);
+            }
+            if account.lamports() > 0 {
+                assert_eq!(stored_account.owner(), account.owner());
+                assert_eq!(stored_account.hash(), hash);
+                assert_eq!(stored_account.rent_epoch(), account.rent_epoch());
+            }
+            index_offset = next;
+        }
+        assert!(reader.get_account(index_offset).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_read_account_data_borrows_mmap() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_read_account_data_borrows_mmap",
+            &[MAX_PERMITTED_DATA_LENGTH],
+        );
+        let reader = tiered_storage.reader().unwrap();
+        let (stored_meta, _) = reader.get_account(IndexOffset(0)).unwrap().unwrap();
+        let data = stored_meta.data();
+        assert_eq!(data.len() as u64, MAX_PERMITTED_DATA_LENGTH);
+        assert_eq!(data, accounts[0].1.data());
+
+        // the account data must be borrowed from the mmap instead of copied
+        let account_blocks = reader
+            .raw_account_block(0, reader.footer().account_blocks_size())
+            .unwrap()
+            .as_ptr_range();
+        let data = data.as_ptr_range();
+        assert!(account_blocks.start <= data.start && data.end <= account_blocks.end);
+    }
+
+    #[test]
+    fn test_sync_and_reopen() {
+        let temp_dir = tempdir().unwrap();
+        // nothing has been written yet, so there is no file to remove on drop
+        let unwritten = ManuallyDrop::new(TieredStorage::new_writable(
+            temp_dir.path().join("test_sync"),
+        ));
+        assert_matches!(unwritten.sync(), Err(TieredStorageError::Io(_)));
+
+        let (tiered_storage, accounts, hashes) =
+            write_test_accounts(&temp_dir, "test_sync_and_reopen", &[1, 2, 3, 1000, 4, 5]);
+        tiered_storage.sync().unwrap();
+
+        {
+            // The original instance owns the file, so the reopened one must
+            // not remove it when dropped.
+            let reopened =
+                ManuallyDrop::new(TieredStorage::new_readonly(tiered_storage.path()).unwrap());
+            let reader = reopened.reader().unwrap();
+            assert_eq!(reader.num_accounts(), accounts.len());
+            for (i, (stored_meta, account)) in accounts.iter().enumerate() {
+                let (stored_account, _) =
+                    reader.get_account(IndexOffset(i as u32)).unwrap().unwrap();
+                assert_eq!(*stored_account.pubkey(), stored_meta.pubkey);
+                verify_account(&stored_account, Some(account), &hashes[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_footer_block_offsets() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) =
+            write_test_accounts(&temp_dir, "test_footer_block_offsets", &[1, 2, 3, 1000]);
+
+        // Open the file the same way an external tool would.  The original
+        // instance owns the file, so the reopened one must not remove it.
+        let reopened =
+            ManuallyDrop::new(TieredStorage::new_readonly(tiered_storage.path()).unwrap());
+        let footer = reopened.footer().unwrap();
+        assert_eq!(footer, tiered_storage.footer().unwrap());
+
+        let address_size = std::mem::size_of::<Pubkey>() as u64;
+        assert_eq!(footer.account_blocks_offset(), 0);
+        assert!(footer.index_block_offset > footer.account_blocks_offset());
+        // the index block holds an address and an u32 offset per account,
+        // padded to 8 bytes.
+        assert_eq!(
+            footer.owners_block_offset - footer.index_block_offset,
+            (accounts.len() as u64 * (address_size + 4)).next_multiple_of(8)
+        );
+        // the owners block is immediately followed by the footer
+        assert_eq!(
+            footer.owners_block_offset + footer.owner_count as u64 * address_size,
+            reopened.file_size().unwrap() - FOOTER_SIZE as u64
+        );
+    }
+
+    #[test]
+    fn test_write_accounts_large_batch() {
+        let account_data_sizes: Vec<_> = (0..10_000).map(|i| i % 100 + 1).collect();
         do_test_write_accounts(
-            "test_write_accounts_mixed_size",
-            &[
-                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 1000, 2000, 3000, 4000, 9, 8, 7, 6, 5, 4, 3, 2, 1,
-            ],
+            "test_write_accounts_large_batch",
+            &account_data_sizes,
             HOT_FORMAT.clone(),
         );
     }
+
+    #[test]
+    fn test_new_readonly_file_too_small() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("test_new_readonly_file_too_small");
+        fs::write(&path, [0xAB; 10]).unwrap();
+
+        assert_matches!(
+            TieredStorage::new_readonly(&path),
+            Err(TieredStorageError::FileTooSmall(file_path, 10)) if file_path == path
+        );
+
+        fs::write(&path, []).unwrap();
+        assert_matches!(
+            TieredStorage::new_readonly(&path),
+            Err(TieredStorageError::FileTooSmall(_, 0))
+        );
+    }
+
+    #[test]
+    fn test_error_source_and_display() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("test_error_source_and_display");
+
+        // the wrapped io::Error is exposed as the source of the error
+        let err = TieredStorage::new_readonly(&path).unwrap_err();
+        let source = std::error::Error::source(&err)
+            .unwrap()
+            .downcast_ref::<std::io::Error>()
+            .unwrap();
+        assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+
+        // errors about a specific file name it
+        fs::write(&path, [0xAB; 10]).unwrap();
+        let err = TieredStorage::new_readonly(&path).unwrap_err();
+        assert!(std::error::Error::source(&err).is_none());
+        assert!(err.to_string().contains(&path.display().to_string()));
+    }
+
+    #[test]
+    fn test_get_stored_account_info() {
+        let account_data_sizes = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 1000, 2000, 3000, 4000, 0];
+        let accounts: Vec<_> = account_data_sizes
+            .iter()
+            .map(|size| create_account(*size))
+            .collect();
+        let account_refs: Vec<_> = accounts
+            .iter()
+            .map(|account| (&account.0.pubkey, &account.1))
+            .collect();
+        let account_data = (Slot::MAX, &account_refs[..]);
+        let storable_accounts =
+            StorableAccountsWithHashesAndWriteVersions::new_with_hashes_and_write_versions(
+                &account_data,
+                std::iter::repeat_with(|| AccountHash(Hash::new_unique()))
+                    .take(accounts.len())
+                    .collect(),
+                vec![StoredMetaWriteVersion::default(); accounts.len()],
+            );
+
+        let temp_dir = tempdir().unwrap();
+        let tiered_storage =
+            TieredStorage::new_writable(temp_dir.path().join("test_get_stored_account_info"));
+        let stored_infos = tiered_storage
+            .write_accounts(&storable_accounts, 0, &HOT_FORMAT)
+            .unwrap();
+
+        // The original instance owns the file, so the reopened one must not
+        // remove it when dropped.
+        let reopened =
+            ManuallyDrop::new(TieredStorage::new_readonly(tiered_storage.path()).unwrap());
+        let reader = reopened.reader().unwrap();
+        for (i, stored_info) in stored_infos.iter().enumerate() {
+            let info = reader
+                .get_stored_account_info(IndexOffset(i as u32))
+                .unwrap()
+                .unwrap();
+            assert_eq!(info.offset, stored_info.offset);
+            assert_eq!(info.size, stored_info.size);
+        }
+        assert_matches!(
+            reader.get_stored_account_info(IndexOffset(stored_infos.len() as u32)),
+            Ok(None)
+        );
+        assert_matches!(
+            reader.get_stored_account_info(IndexOffset(stored_infos.len() as u32 + 1)),
+            Err(TieredStorageError::IndexOffsetOutOfBounds(_, _))
+        );
+    }
+
+    #[test]
+    fn test_copy_to() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) =
+            write_test_accounts(&temp_dir, "test_copy_to", &[1, 2, 3, 4, 1000, 2000, 5]);
+        let dest = temp_dir.path().join("test_copy_to_dest");
+
+        {
+            let copy = tiered_storage.copy_to(&dest).unwrap();
+            assert!(copy.is_read_only());
+            assert_eq!(copy.path(), dest);
+            assert_eq!(
+                copy.file_size().unwrap(),
+                tiered_storage.file_size().unwrap()
+            );
+
+            let reader = copy.reader().unwrap();
+            assert_eq!(
+                reader.num_accounts(),
+                tiered_storage.reader().unwrap().num_accounts()
+            );
+            for (i, (stored_meta, account)) in accounts.iter().enumerate() {
+                let (stored_account, _) =
+                    reader.get_account(IndexOffset(i as u32)).unwrap().unwrap();
+                assert_eq!(*stored_account.pubkey(), stored_meta.pubkey);
+                verify_account(&stored_account, Some(account), &hashes[i]);
+            }
+        }
+
+        // dropping the copy only removes its own file
+        assert!(!dest.exists());
+        assert!(tiered_storage.path().exists());
+
+        // the source file must exist
+        let unwritten = ManuallyDrop::new(TieredStorage::new_writable(
+            temp_dir.path().join("test_copy_to_unwritten"),
+        ));
+        assert_matches!(unwritten.copy_to(&dest), Err(TieredStorageError::Io(_)));
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_new_readonly_from_file() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) = write_test_accounts(
+            &temp_dir,
+            "test_new_readonly_from_file",
+            &[1, 2, 3, 4, 1000, 2000, 5],
+        );
+
+        {
+            let file = File::open(tiered_storage.path()).unwrap();
+            let from_file = TieredStorage::new_readonly_from_file(file).unwrap();
+            assert!(from_file.is_read_only());
+
+            let reader = from_file.reader().unwrap();
+            assert_eq!(reader.num_accounts(), accounts.len());
+            for (i, (stored_meta, account)) in accounts.iter().enumerate() {
+                let (stored_account, _) =
+                    reader.get_account(IndexOffset(i as u32)).unwrap().unwrap();
+                assert_eq!(*stored_account.pubkey(), stored_meta.pubkey);
+                verify_account(&stored_account, Some(account), &hashes[i]);
+            }
+        }
+
+        // the caller owns the file, so dropping the instance must not remove it
+        assert!(tiered_storage.path().exists());
+    }
+
+    #[test]
+    fn test_new_readonly_keep() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) =
+            write_test_accounts(&temp_dir, "test_new_readonly_keep", &[1, 2, 3, 4, 1000]);
+
+        {
+            let first = TieredStorage::new_readonly_keep(tiered_storage.path()).unwrap();
+            let second = TieredStorage::new_readonly_keep(tiered_storage.path()).unwrap();
+            assert!(first.is_read_only());
+            assert_eq!(first.path(), second.path());
+            assert_eq!(first.reader().unwrap().num_accounts(), accounts.len());
+            assert_eq!(second.reader().unwrap().num_accounts(), accounts.len());
+
+            drop(first);
+            assert!(tiered_storage.path().exists());
+            assert_eq!(second.reader().unwrap().num_accounts(), accounts.len());
+        }
+
+        // neither handle removed the file
+        assert!(tiered_storage.path().exists());
+    }
+
+    #[test]
+    fn test_files_equivalent() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) = write_test_accounts(
+            &temp_dir,
+            "test_files_equivalent",
+            &[1, 2, 3, 4, 1000, 2000, 5],
+        );
+        let reader = tiered_storage.reader().unwrap();
+        assert!(files_equivalent(reader, reader).unwrap());
+
+        // the same accounts indexed in a different order
+        let reversed_accounts: Vec<_> = accounts.iter().rev().cloned().collect();
+        let reversed_hashes: Vec<_> = hashes.iter().rev().copied().collect();
+        let reversed = write_accounts_with_hashes(
+            &temp_dir,
+            "test_files_equivalent_reversed",
+            &reversed_accounts,
+            &reversed_hashes,
+        );
+        assert!(files_equivalent(reader, reversed.reader().unwrap()).unwrap());
+
+        // one account with different lamports
+        let mut modified_accounts = accounts.clone();
+        let lamports = modified_accounts[3].1.lamports();
+        modified_accounts[3].1.set_lamports(lamports + 1);
+        let modified = write_accounts_with_hashes(
+            &temp_dir,
+            "test_files_equivalent_modified",
+            &modified_accounts,
+            &hashes,
+        );
+        assert!(!files_equivalent(reader, modified.reader().unwrap()).unwrap());
+        assert!(!files_equivalent(modified.reader().unwrap(), reader).unwrap());
+    }
+
+    /// Overwrites the account offset of the index entry at `i` in the file at
+    /// `path` so that it points beyond the account blocks.
+    fn corrupt_account_offset(path: &Path, i: usize) {
+        let footer = TieredStorageFooter::new_from_path(path).unwrap();
+        // the raw offsets follow the addresses in the index block
+        let position = footer.index_block_offset as usize
+            + footer.account_entry_count as usize * std::mem::size_of::<Pubkey>()
+            + i * std::mem::size_of::<u32>();
+        let mut file = OpenOptions::new().write(true).open(path).unwrap();
+        file.seek(SeekFrom::Start(position as u64)).unwrap();
+        file.write_all(&u32::MAX.to_le_bytes()).unwrap();
+    }
+
+    /// Flips one byte of the account hash `hash` stored in the file at `path`.
+    fn corrupt_stored_hash(path: &Path, hash: &AccountHash) {
+        let bytes = fs::read(path).unwrap();
+        let position = bytes
+            .windows(std::mem::size_of::<AccountHash>())
+            .position(|window| window == hash.0.as_ref())
+            .unwrap();
+        let mut file = OpenOptions::new().write(true).open(path).unwrap();
+        file.seek(SeekFrom::Start(position as u64)).unwrap();
+        file.write_all(&[!bytes[position]]).unwrap();
+    }
+
+    #[test]
+    fn test_verify_hashes() {
+        let temp_dir = tempdir().unwrap();
+        let accounts: Vec<_> = [1, 2, 3, 4, 1000, 2000, 5]
+            .iter()
+            .map(|size| create_account(*size))
+            .collect();
+        let hashes: Vec<_> = accounts
+            .iter()
+            .map(|(stored_meta, account)| AccountsDb::hash_account(account, &stored_meta.pubkey))
+            .collect();
+        let tiered_storage =
+            write_accounts_with_hashes(&temp_dir, "test_verify_hashes", &accounts, &hashes);
+        assert!(tiered_storage
+            .reader()
+            .unwrap()
+            .verify_hashes()
+            .unwrap()
+            .is_empty());
+
+        corrupt_stored_hash(tiered_storage.path(), &hashes[4]);
+        let reader = TieredStorageReader::new_from_path(tiered_storage.path()).unwrap();
+        assert_eq!(reader.verify_hashes().unwrap(), vec![accounts[4].0.pubkey]);
+    }
+
+    #[test]
+    fn test_validate_index() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_validate_index",
+            &[1, 2, 3, 4, 1000, 2000, 5],
+        );
+        let reader = tiered_storage.reader().unwrap();
+        assert!(reader.validate_index().is_ok());
+
+        // the raw offsets follow the addresses in the index block
+        let offsets_position = reader.footer().index_block_offset as usize
+            + accounts.len() * std::mem::size_of::<Pubkey>();
+        let offset_entry_size = std::mem::size_of::<u32>();
+        let overwrite_offset_entry = |i: usize, raw_offset: &[u8]| {
+            let mut file = OpenOptions::new()
+                .write(true)
+                .open(tiered_storage.path())
+                .unwrap();
+            file.seek(SeekFrom::Start(
+                (offsets_position + i * offset_entry_size) as u64,
+            ))
+            .unwrap();
+            file.write_all(raw_offset).unwrap();
+        };
+        let bytes = fs::read(tiered_storage.path()).unwrap();
+        let raw_offset =
+            |i: usize| &bytes[offsets_position + i * offset_entry_size..][..offset_entry_size];
+
+        // an offset pointing beyond the account blocks
+        overwrite_offset_entry(3, &u32::MAX.to_le_bytes());
+        let reader = TieredStorageReader::new_from_path(tiered_storage.path()).unwrap();
+        assert_matches!(
+            reader.validate_index(),
+            Err(TieredStorageError::CorruptIndex(3, _))
+        );
+
+        // an offset overlapping the previous account
+        overwrite_offset_entry(3, raw_offset(2));
+        let reader = TieredStorageReader::new_from_path(tiered_storage.path()).unwrap();
+        assert_matches!(
+            reader.validate_index(),
+            Err(TieredStorageError::CorruptIndex(3, _))
+        );
+
+        // the intact offset makes the index valid again
+        overwrite_offset_entry(3, raw_offset(3));
+        let reader = TieredStorageReader::new_from_path(tiered_storage.path()).unwrap();
+        assert!(reader.validate_index().is_ok());
+    }
+
+    #[test]
+    fn test_new_best_effort_from_path() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_new_best_effort_from_path",
+            &[1, 2, 3, 4, 1000, 2000, 5],
+        );
+        let pubkeys = |reader: &TieredStorageReader| -> Vec<Pubkey> {
+            reader
+                .accounts(IndexOffset(0))
+                .unwrap()
+                .iter()
+                .map(|account| *account.pubkey())
+                .collect()
+        };
+
+        // an intact file behaves the same as with new_from_path()
+        let (reader, errors) =
+            TieredStorageReader::new_best_effort_from_path(tiered_storage.path()).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(
+            pubkeys(&reader),
+            pubkeys(&TieredStorageReader::new_from_path(tiered_storage.path()).unwrap())
+        );
+
+        // corrupt the offset of the account at index 3
+        corrupt_account_offset(tiered_storage.path(), 3);
+
+        let (reader, errors) =
+            TieredStorageReader::new_best_effort_from_path(tiered_storage.path()).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_matches!(errors[0], TieredStorageError::CorruptIndex(3, _));
+        // the account at index 2 is skipped too, as its block ends at the
+        // corrupt offset
+        let expected_accounts: Vec<_> = accounts
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != 2 && *i != 3)
+            .map(|(_, account)| account)
+            .collect();
+        let salvaged_accounts = reader.accounts(IndexOffset(0)).unwrap();
+        assert_eq!(salvaged_accounts.len(), expected_accounts.len());
+        for (account, (stored_meta, expected_account)) in
+            salvaged_accounts.iter().zip(expected_accounts)
+        {
+            assert_eq!(account.pubkey(), &stored_meta.pubkey);
+            assert_eq!(account.lamports(), expected_account.lamports());
+            assert_eq!(account.data(), expected_account.data());
+        }
+        assert_eq!(
+            reader.find_index_offset(&accounts[3].0.pubkey).unwrap(),
+            None
+        );
+        assert_eq!(
+            reader.find_index_offset(&accounts[4].0.pubkey).unwrap(),
+            Some(IndexOffset(4))
+        );
+
+        // the footer must still be valid
+        let file = OpenOptions::new()
+            .write(true)
+            .open(tiered_storage.path())
+            .unwrap();
+        file.set_len(FOOTER_SIZE as u64 - 1).unwrap();
+        assert!(TieredStorageReader::new_best_effort_from_path(tiered_storage.path()).is_err());
+    }
+
+    #[test]
+    fn test_best_effort_reader_apis() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) = write_test_accounts(
+            &temp_dir,
+            "test_best_effort_reader_apis",
+            &[1, 2, 3, 4, 1000, 2000, 5],
+        );
+        corrupt_account_offset(tiered_storage.path(), 3);
+        let addresses: Vec<_> = accounts
+            .iter()
+            .map(|(stored_meta, _)| stored_meta.pubkey)
+            .collect();
+        // the account at index 2 is skipped along with the corrupt one
+        let is_salvaged = |i: usize| i != 2 && i != 3;
+        let salvaged_addresses: Vec<_> = (0..accounts.len())
+            .filter(|i| is_salvaged(*i))
+            .map(|i| addresses[i])
+            .collect();
+
+        let (reader, errors) =
+            TieredStorageReader::new_best_effort_from_path(tiered_storage.path()).unwrap();
+        assert_eq!(errors.len(), 1);
+
+        let found = reader.get_accounts(&addresses).unwrap();
+        for (i, account) in found.iter().enumerate() {
+            assert_eq!(
+                account.as_ref().map(|account| *account.pubkey()),
+                is_salvaged(i).then_some(addresses[i])
+            );
+        }
+
+        for (i, (stored_meta, account)) in accounts.iter().enumerate() {
+            let owned = reader.accounts_by_owner(account.owner()).unwrap();
+            if is_salvaged(i) {
+                assert_eq!(owned.len(), 1);
+                assert_eq!(*owned[0].pubkey(), stored_meta.pubkey);
+                verify_account(&owned[0], Some(account), &hashes[i]);
+            } else {
+                assert!(owned.is_empty());
+            }
+        }
+
+        let not_owned: Vec<_> = reader
+            .accounts_not_owned_by(&HashSet::from([*accounts[0].1.owner()]))
+            .map(|account| *account.unwrap().pubkey())
+            .collect();
+        assert_eq!(not_owned, salvaged_addresses[1..]);
+
+        let expected_data_bytes: usize = (0..accounts.len())
+            .filter(|i| is_salvaged(*i))
+            .map(|i| accounts[i].1.data().len())
+            .sum();
+        assert_eq!(
+            reader.efficiency_report().unwrap().data_bytes,
+            expected_data_bytes
+        );
+
+        assert_eq!(reader.get_hash(&addresses[3]).unwrap(), None);
+        assert_eq!(reader.get_hash(&addresses[4]).unwrap(), Some(hashes[4]));
+        assert_eq!(
+            reader.account_matches_owners(IndexOffset(3), &[*accounts[3].1.owner()]),
+            Err(MatchAccountOwnerError::UnableToLoad)
+        );
+        assert_eq!(
+            reader.account_matches_owners(IndexOffset(4), &[*accounts[4].1.owner()]),
+            Ok(0)
+        );
+        // the test accounts have random hashes, so none of them verifies
+        assert_eq!(reader.verify_hashes().unwrap(), salvaged_addresses);
+        assert_matches!(
+            reader.validate_index(),
+            Err(TieredStorageError::CorruptIndex(3, _))
+        );
+
+        // without skipping corrupt entries, the corrupt entry is reported
+        // as an error instead of causing a panic
+        let reader = TieredStorageReader::new_from_path(tiered_storage.path()).unwrap();
+        assert!(reader.get_accounts(&addresses).is_err());
+        assert_matches!(
+            reader.accounts_by_owner(accounts[4].1.owner()),
+            Err(TieredStorageError::CorruptIndex(3, _))
+        );
+        assert!(reader
+            .accounts_not_owned_by(&HashSet::new())
+            .any(|account| account.is_err()));
+        assert!(reader.efficiency_report().is_err());
+        assert!(reader.verify_hashes().is_err());
+    }
+
+    #[test]
+    fn test_get_account_prefetched() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_get_account_prefetched",
+            &[1, 2, 3, 4, 1000, 2000, 5, 0, 10_000],
+        );
+        let reader = tiered_storage.reader().unwrap();
+
+        let mut index_offset = IndexOffset(0);
+        let mut num_accounts = 0;
+        while let Some((account, next)) = reader.get_account(index_offset).unwrap() {
+            let (prefetched, prefetched_next) = reader
+                .get_account_prefetched(index_offset)
+                .unwrap()
+                .unwrap();
+            assert_eq!(prefetched, account);
+            assert_eq!(prefetched_next, next);
+            index_offset = next;
+            num_accounts += 1;
+        }
+        assert_eq!(num_accounts, accounts.len());
+        assert_eq!(reader.get_account_prefetched(index_offset).unwrap(), None);
+        assert_matches!(
+            reader.get_account_prefetched(IndexOffset(index_offset.0 + 1)),
+            Err(TieredStorageError::IndexOffsetOutOfBounds(_, _))
+        );
+    }
+
+    #[test]
+    fn test_write_accounts_with_footer() {
+        let temp_dir = tempdir().unwrap();
+        let accounts: Vec<_> = [1, 2, 3, 4, 1000, 2000, 5, 0]
+            .iter()
+            .map(|size| create_account(*size))
+            .collect();
+        let account_refs: Vec<_> = accounts
+            .iter()
+            .map(|account| (&account.0.pubkey, &account.1))
+            .collect();
+        let account_data = (Slot::MAX, &account_refs[..]);
+        let hashes: Vec<_> = std::iter::repeat_with(|| AccountHash(Hash::new_unique()))
+            .take(accounts.len())
+            .collect();
+        let write_versions: Vec<_> = accounts
+            .iter()
+            .map(|account| account.0.write_version_obsolete)
+            .collect();
+        let storable_accounts =
+            StorableAccountsWithHashesAndWriteVersions::new_with_hashes_and_write_versions(
+                &account_data,
+                hashes,
+                write_versions,
+            );
+
+        let tiered_storage =
+            TieredStorage::new_writable(temp_dir.path().join("test_write_accounts_with_footer"));
+        let (stored_infos, footer) = tiered_storage
+            .write_accounts_with_footer(&storable_accounts, 0, &HOT_FORMAT)
+            .unwrap();
+        assert_eq!(stored_infos.len(), accounts.len());
+        assert_eq!(footer.account_meta_format, HOT_FORMAT.account_meta_format);
+        assert_eq!(footer.owners_block_format, HOT_FORMAT.owners_block_format);
+        assert_eq!(footer.index_block_format, HOT_FORMAT.index_block_format);
+        assert_eq!(footer.account_block_format, HOT_FORMAT.account_block_format);
+        assert_eq!(footer.account_entry_count as usize, accounts.len());
+
+        // the returned footer is the one persisted in the file
+        assert_eq!(tiered_storage.footer(), Some(&footer));
+    }
+
+    #[test]
+    fn test_contains() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) =
+            write_test_accounts(&temp_dir, "test_contains", &[1, 2, 3, 4, 1000, 2000, 0]);
+        let reader = tiered_storage.reader().unwrap();
+
+        for (stored_meta, _) in &accounts {
+            assert!(reader.contains(&stored_meta.pubkey).unwrap());
+        }
+        assert!(!reader.contains(&Pubkey::new_unique()).unwrap());
+    }
+
+    #[test]
+    fn test_slot() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) =
+            write_test_accounts(&temp_dir, "test_slot_max", &[1, 2, 3]);
+        assert_eq!(tiered_storage.reader().unwrap().slot(), Slot::MAX);
+
+        let slot: Slot = 123_456;
+        let tiered_storage = TieredStorage::new_writable(temp_dir.path().join("test_slot"));
+        write_accounts_for_slot(&tiered_storage, slot, &accounts, &hashes);
+        assert_eq!(tiered_storage.reader().unwrap().slot(), slot);
+
+        let reader = TieredStorageReader::new_from_path(tiered_storage.path()).unwrap();
+        assert_eq!(reader.slot(), slot);
+    }
+
+    #[test]
+    fn test_read_v1_footer() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) =
+            write_test_accounts(&temp_dir, "test_read_v1_footer", &[1, 2, 3, 4, 1000]);
+        let footer = *tiered_storage.reader().unwrap().footer();
+
+        // replace the footer with the one an older writer would have written
+        let file = OpenOptions::new()
+            .append(true)
+            .open(tiered_storage.path())
+            .unwrap();
+        let file_size = file.metadata().unwrap().len();
+        file.set_len(file_size - FOOTER_SIZE as u64).unwrap();
+        footer
+            .write_v1_footer_block(&TieredStorageFile(file))
+            .unwrap();
+
+        let reader = TieredStorageReader::new_from_path(tiered_storage.path()).unwrap();
+        assert_eq!(reader.footer().format_version, 1);
+        assert_eq!(reader.slot(), 0);
+        assert_eq!(reader.num_accounts(), accounts.len());
+        for (i, ((stored_meta, account), hash)) in accounts.iter().zip(&hashes).enumerate() {
+            let (stored_account, _) = reader.get_account(IndexOffset(i as u32)).unwrap().unwrap();
+            assert_eq!(stored_account.pubkey(), &stored_meta.pubkey);
+            verify_account(&stored_account, Some(account), hash);
+        }
+    }
+
+    #[test]
+    fn test_get_hash() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) =
+            write_test_accounts(&temp_dir, "test_get_hash", &[1, 2, 3, 4, 1000, 2000, 0]);
+        let reader = tiered_storage.reader().unwrap();
+
+        for ((stored_meta, account), hash) in accounts.iter().zip(&hashes) {
+            let expected_hash = if account.lamports() == 0 {
+                // zero-lamport accounts are stored without their hash
+                AccountHash(Hash::default())
+            } else {
+                *hash
+            };
+            assert_eq!(
+                reader.get_hash(&stored_meta.pubkey).unwrap(),
+                Some(expected_hash)
+            );
+        }
+        assert_eq!(reader.get_hash(&Pubkey::new_unique()).unwrap(), None);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_get_account_async() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, _accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_get_account_async",
+            &[1, 2, 3, 4, 1000, 2000, 0, 5, 9, 10_000],
+        );
+        let reader = tiered_storage.reader().unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        for i in 0..=reader.num_accounts() {
+            let index_offset = IndexOffset(i as u32);
+            let expected = reader.get_account(index_offset).unwrap();
+            let actual = runtime
+                .block_on(reader.get_account_async(index_offset))
+                .unwrap();
+            match (expected, actual) {
+                (Some((expected, expected_next)), Some((actual, actual_next))) => {
+                    assert_eq!(actual.pubkey(), expected.pubkey());
+                    assert_eq!(actual.lamports(), expected.lamports());
+                    assert_eq!(actual.owner(), expected.owner());
+                    assert_eq!(actual.data(), expected.data());
+                    assert_eq!(actual.hash(), expected.hash());
+                    assert_eq!(actual_next, expected_next);
+                }
+                (None, None) => assert_eq!(i, reader.num_accounts()),
+                _ => panic!("get_account_async() differs from get_account() at {i}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_scan_accounts() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_scan_accounts",
+            &[1, 2, 3, 4, 1000, 2000, 0, 5],
+        );
+        let reader = tiered_storage.reader().unwrap();
+
+        let mut pubkeys = vec![];
+        reader
+            .scan_accounts(|account| pubkeys.push(*account.pubkey()))
+            .unwrap();
+        let expected_pubkeys: Vec<_> = accounts
+            .iter()
+            .map(|(stored_meta, _)| stored_meta.pubkey)
+            .collect();
+        assert_eq!(pubkeys, expected_pubkeys);
+    }
+
+    #[test]
+    fn test_write_version_not_persisted() {
+        let temp_dir = tempdir().unwrap();
+        let mut accounts: Vec<_> = [0, 1, 100].into_iter().map(create_account).collect();
+        for (i, (stored_meta, _account)) in accounts.iter_mut().enumerate() {
+            stored_meta.write_version_obsolete = 42 + i as StoredMetaWriteVersion;
+        }
+        let (tiered_storage, _hashes) =
+            write_accounts_to(&temp_dir, "test_write_version_not_persisted", &accounts);
+
+        // hot accounts files do not store write versions, so all accounts
+        // are read back with the default one regardless of what was written.
+        let stored_accounts = tiered_storage
+            .reader()
+            .unwrap()
+            .accounts(IndexOffset(0))
+            .unwrap();
+        assert_eq!(stored_accounts.len(), accounts.len());
+        for stored_account in stored_accounts {
+            assert_eq!(
+                stored_account.write_version(),
+                StoredMetaWriteVersion::default()
+            );
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let temp_dir = tempdir().unwrap();
+        let accounts_a: Vec<_> = [1, 2, 3, 1000].into_iter().map(create_account).collect();
+        let mut accounts_b: Vec<_> = [4, 5, 2000].into_iter().map(create_account).collect();
+        // the last account of b overrides the second account of a
+        accounts_b[2].0.pubkey = accounts_a[1].0.pubkey;
+        let (tiered_storage_a, _hashes_a) =
+            write_accounts_to(&temp_dir, "test_merge_a", &accounts_a);
+        let (tiered_storage_b, hashes_b) =
+            write_accounts_to(&temp_dir, "test_merge_b", &accounts_b);
+
+        let merged = TieredStorage::merge(
+            &[
+                tiered_storage_a.reader().unwrap(),
+                tiered_storage_b.reader().unwrap(),
+            ],
+            temp_dir.path().join("test_merge"),
+            &HOT_FORMAT,
+        )
+        .unwrap();
+        assert!(merged.is_read_only());
+        let account_map = merged.reader().unwrap().to_account_map().unwrap();
+
+        let expected_pubkeys: HashSet<_> = accounts_a
+            .iter()
+            .chain(&accounts_b)
+            .map(|(stored_meta, _)| stored_meta.pubkey)
+            .collect();
+        assert_eq!(account_map.len(), expected_pubkeys.len());
+        assert_eq!(account_map.len(), accounts_a.len() + accounts_b.len() - 1);
+        assert!(account_map
+            .keys()
+            .all(|pubkey| expected_pubkeys.contains(pubkey)));
+
+        let overlap = &account_map[&accounts_a[1].0.pubkey];
+        let (_, expected_account) = &accounts_b[2];
+        assert_eq!(overlap.lamports(), expected_account.lamports());
+        assert_eq!(overlap.data(), expected_account.data());
+        assert_eq!(overlap.owner(), expected_account.owner());
+        assert_eq!(overlap.hash(), &hashes_b[2]);
+
+        // unsupported formats do not leave a file behind
+        let dest = temp_dir.path().join("test_merge_unsupported");
+        let format = TieredStorageFormat {
+            index_block_format: IndexBlockFormat::SortedAddressesThenOffsets,
+            ..HOT_FORMAT.clone()
+        };
+        assert_matches!(
+            TieredStorage::merge(&[tiered_storage_a.reader().unwrap()], &dest, &format),
+            Err(TieredStorageError::UnknownFormat(_))
+        );
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_to_account_map() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) = write_test_accounts(
+            &temp_dir,
+            "test_to_account_map",
+            &[1, 2, 3, 4, 1000, 2000, 0, 5],
+        );
+        let reader = tiered_storage.reader().unwrap();
+        let account_map = reader.to_account_map().unwrap();
+
+        assert_eq!(
+            account_map.keys().collect::<HashSet<_>>(),
+            accounts
+                .iter()
+                .map(|(stored_meta, _)| &stored_meta.pubkey)
+                .collect::<HashSet<_>>()
+        );
+        for ((stored_meta, account), hash) in accounts.iter().zip(&hashes) {
+            let stored_account = &account_map[&stored_meta.pubkey];
+            assert_eq!(stored_account.pubkey(), &stored_meta.pubkey);
+            assert_eq!(stored_account.lamports(), account.lamports());
+            assert_eq!(stored_account.data(), account.data());
+            if account.lamports() > 0 {
+                assert_eq!(stored_account.owner(), account.owner());
+                assert_eq!(stored_account.hash(), hash);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_account_range() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_get_account_range",
+            &[1, 2, 3, 4, 1000, 2000, 0, 5, 9],
+        );
+        let reader = tiered_storage.reader().unwrap();
+        let pubkeys = |range: Vec<StoredAccountMeta>| -> Vec<Pubkey> {
+            range.iter().map(|account| *account.pubkey()).collect()
+        };
+
+        let mut sequential = vec![];
+        let mut index_offset = IndexOffset(2);
+        for _ in 0..4 {
+            let (account, next) = reader.get_account(index_offset).unwrap().unwrap();
+            sequential.push(*account.pubkey());
+            index_offset = next;
+        }
+        let range = reader.get_account_range(IndexOffset(2), 4).unwrap();
+        assert_eq!(pubkeys(range), sequential);
+
+        // ranges are truncated at the end of the file
+        let range = reader.get_account_range(IndexOffset(7), 10).unwrap();
+        assert_eq!(
+            pubkeys(range),
+            vec![accounts[7].0.pubkey, accounts[8].0.pubkey]
+        );
+        assert!(reader
+            .get_account_range(IndexOffset(0), 0)
+            .unwrap()
+            .is_empty());
+        // ranges starting at or past the end are empty
+        let num_accounts = accounts.len() as u32;
+        assert!(reader
+            .get_account_range(IndexOffset(num_accounts), 1)
+            .unwrap()
+            .is_empty());
+        assert!(reader
+            .get_account_range(IndexOffset(num_accounts + 10), 1)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_lookup_cache() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) =
+            write_test_accounts(&temp_dir, "test_lookup_cache", &[1, 2, 3, 4, 1000, 2000, 0]);
+
+        // the lookup cache is disabled by default
+        assert!(tiered_storage.reader().unwrap().lookup_cache().is_none());
+
+        let reader = TieredStorageReader::new_from_path(tiered_storage.path())
+            .unwrap()
+            .with_lookup_cache(4);
+        let address = &accounts[5].0.pubkey;
+
+        let first = reader.find_index_offset(address).unwrap();
+        assert_eq!(first, Some(IndexOffset(5)));
+        let cache = reader.lookup_cache().unwrap();
+        assert_eq!(cache.hits(), 0);
+        assert_eq!(cache.misses(), 1);
+
+        let second = reader.find_index_offset(address).unwrap();
+        assert_eq!(second, first);
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(cache.misses(), 1);
+
+        // addresses not in the file are never cached
+        assert_eq!(
+            reader.find_index_offset(&Pubkey::new_unique()).unwrap(),
+            None
+        );
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_seal_into_readonly() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) = write_test_accounts(
+            &temp_dir,
+            "test_seal_into_readonly",
+            &[1, 2, 3, 4, 1000, 2000, 5],
+        );
+        let path = tiered_storage.path().to_path_buf();
+
+        let sealed = tiered_storage.seal_into_readonly().unwrap();
+        assert!(sealed.is_read_only());
+        assert_eq!(sealed.path(), path);
+        assert!(path.exists());
+
+        let reader = sealed.reader().unwrap();
+        assert_eq!(reader.num_accounts(), accounts.len());
+        for (i, (stored_meta, account)) in accounts.iter().enumerate() {
+            let (stored_account, _) = reader.get_account(IndexOffset(i as u32)).unwrap().unwrap();
+            assert_eq!(*stored_account.pubkey(), stored_meta.pubkey);
+            verify_account(&stored_account, Some(account), &hashes[i]);
+        }
+
+        // the sealed instance owns the file now
+        drop(sealed);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_fsync_policy() {
+        let temp_dir = tempdir().unwrap();
+        let accounts: Vec<_> = [1, 2, 100, 1000, 2000]
+            .into_iter()
+            .map(create_account)
+            .collect();
+        let hashes = new_account_hashes(accounts.len());
+
+        let sealed: Vec<_> = [FsyncPolicy::Always, FsyncPolicy::Never, FsyncPolicy::OnSeal]
+            .into_iter()
+            .map(|fsync_policy| {
+                let tiered_storage = TieredStorage::new_writable(
+                    temp_dir
+                        .path()
+                        .join(format!("test_fsync_policy_{fsync_policy:?}")),
+                )
+                .with_fsync_policy(fsync_policy);
+                write_accounts_for_slot(&tiered_storage, Slot::MAX, &accounts, &hashes);
+                tiered_storage.seal_into_readonly().unwrap()
+            })
+            .collect();
+
+        // the policy only decides when the file is synced, so all the files
+        // read back the same accounts.
+        for tiered_storage in &sealed {
+            assert!(files_equivalent(
+                sealed[0].reader().unwrap(),
+                tiered_storage.reader().unwrap()
+            )
+            .unwrap());
+            let reader = tiered_storage.reader().unwrap();
+            assert_eq!(reader.num_accounts(), accounts.len());
+            for (i, (stored_meta, account)) in accounts.iter().enumerate() {
+                let (stored_account, _) =
+                    reader.get_account(IndexOffset(i as u32)).unwrap().unwrap();
+                assert_eq!(*stored_account.pubkey(), stored_meta.pubkey);
+                verify_account(&stored_account, Some(account), &hashes[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_recover() {
+        let temp_dir = tempdir().unwrap();
+        let test_cases: [&[u64]; 2] = [&[1, 2, 3, 4, 1000, 2000, 5], &[1, 100]];
+        for (i, account_data_sizes) in test_cases.into_iter().enumerate() {
+            // a file whose footer is gone entirely loses its slot, while a
+            // torn footer that still holds the slot keeps it
+            for (footer_bytes_lost, expected_slot) in [(FOOTER_SIZE, 0), (8, Slot::MAX)] {
+                let path = temp_dir
+                    .path()
+                    .join(format!("test_recover_{i}_{footer_bytes_lost}"));
+                let accounts: Vec<_> = account_data_sizes
+                    .iter()
+                    .map(|size| create_account(*size))
+                    .collect();
+                let hashes = write_hot_accounts(HotStorageWriter::new(&path).unwrap(), &accounts);
+                let expected_footer = TieredStorageFooter::new_from_path(&path).unwrap();
+
+                let file_size = fs::metadata(&path).unwrap().len();
+                let file = OpenOptions::new().write(true).open(&path).unwrap();
+                file.set_len(file_size - footer_bytes_lost as u64).unwrap();
+                drop(file);
+                assert!(TieredStorage::new_readonly_keep(&path).is_err());
+                let corrupt_bytes = fs::read(&path).unwrap();
+
+                assert_eq!(
+                    TieredStorage::recover(&path).unwrap(),
+                    RecoveryOutcome::Recovered {
+                        num_accounts: accounts.len()
+                    }
+                );
+                // the original file is kept
+                let corrupt_path = temp_dir
+                    .path()
+                    .join(format!("test_recover_{i}_{footer_bytes_lost}.corrupt"));
+                assert_eq!(fs::read(&corrupt_path).unwrap(), corrupt_bytes);
+
+                let recovered = TieredStorage::new_readonly(&path).unwrap();
+                let footer = recovered.footer().unwrap();
+                assert_eq!(footer.slot, expected_slot);
+                assert_eq!(
+                    footer.owners_block_format,
+                    expected_footer.owners_block_format
+                );
+                assert_eq!(
+                    footer.owners_block_offset,
+                    expected_footer.owners_block_offset
+                );
+                assert_eq!(footer.owner_count, expected_footer.owner_count);
+
+                let reader = recovered.reader().unwrap();
+                assert_eq!(reader.num_accounts(), accounts.len());
+                for (i, (stored_meta, account)) in accounts.iter().enumerate() {
+                    let (stored_account, _) =
+                        reader.get_account(IndexOffset(i as u32)).unwrap().unwrap();
+                    assert_eq!(*stored_account.pubkey(), stored_meta.pubkey);
+                    verify_account(&stored_account, Some(account), &hashes[i]);
+                }
+
+                // the recovered file is valid, and is left as is
+                let file_bytes = fs::read(&path).unwrap();
+                assert_eq!(
+                    TieredStorage::recover(&path).unwrap(),
+                    RecoveryOutcome::AlreadyValid
+                );
+                assert_eq!(fs::read(&path).unwrap(), file_bytes);
+            }
+        }
+
+        // a file that was never a tiered accounts file
+        let path = temp_dir.path().join("test_recover_unrecoverable");
+        fs::write(&path, [u8::MAX; 1024]).unwrap();
+        assert_matches!(
+            TieredStorage::recover(&path),
+            Err(TieredStorageError::Unrecoverable(unrecoverable_path)) if unrecoverable_path == path
+        );
+        assert_eq!(fs::read(&path).unwrap(), [u8::MAX; 1024]);
+        assert!(!temp_dir
+            .path()
+            .join("test_recover_unrecoverable.corrupt")
+            .try_exists()
+            .unwrap());
+    }
+
+    #[test]
+    fn test_raw_account_block() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, _accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_raw_account_block",
+            &[1, 2, 3, 4, 1000, 2000, 5],
+        );
+        let reader = tiered_storage.reader().unwrap();
+        let account_blocks_size = reader.footer().account_blocks_size();
+        assert_eq!(account_blocks_size, reader.footer().index_block_offset);
+
+        let account_blocks = reader.raw_account_block(0, account_blocks_size).unwrap();
+        assert_eq!(account_blocks.len() as u64, account_blocks_size);
+        let file_bytes = fs::read(tiered_storage.path()).unwrap();
+        assert_eq!(account_blocks, &file_bytes[..account_blocks_size as usize]);
+
+        // a sub-range of the account blocks
+        assert_eq!(reader.raw_account_block(8, 16).unwrap(), &file_bytes[8..24]);
+        assert!(reader
+            .raw_account_block(account_blocks_size, 0)
+            .unwrap()
+            .is_empty());
+
+        // ranges beyond the account blocks are rejected
+        assert_matches!(
+            reader.raw_account_block(0, account_blocks_size + 1),
+            Err(TieredStorageError::AccountBlockRangeOutOfBounds(0, len, size))
+                if len == account_blocks_size + 1 && size == account_blocks_size
+        );
+        assert_matches!(
+            reader.raw_account_block(u64::MAX, 1),
+            Err(TieredStorageError::AccountBlockRangeOutOfBounds(..))
+        );
+    }
+
+    #[test]
+    fn test_accounts_with_offsets() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) = write_test_accounts(
+            &temp_dir,
+            "test_accounts_with_offsets",
+            &[1, 2, 3, 4, 1000, 2000, 5],
+        );
+        let reader = tiered_storage.reader().unwrap();
+
+        let accounts_with_offsets: Vec<_> = reader
+            .accounts_with_offsets()
+            .collect::<TieredStorageResult<_>>()
+            .unwrap();
+        assert_eq!(accounts_with_offsets.len(), accounts.len());
+
+        let meta_size = std::mem::size_of::<HotAccountMeta>() as u64;
+        for (i, (stored_account, index_offset, account_block_offset)) in
+            accounts_with_offsets.iter().enumerate()
+        {
+            assert_eq!(*index_offset, IndexOffset(i as u32));
+            let (expected_account, _) = reader.get_account(*index_offset).unwrap().unwrap();
+            assert_eq!(stored_account, &expected_account);
+            verify_account(stored_account, Some(&accounts[i].1), &hashes[i]);
+
+            // the account block starts with the account meta, followed by
+            // the account data
+            let StoredAccountMeta::Hot(hot_account) = stored_account else {
+                panic!("unexpected account type");
+            };
+            assert_eq!(
+                reader
+                    .raw_account_block(*account_block_offset, meta_size)
+                    .unwrap(),
+                bytemuck::bytes_of(hot_account.meta)
+            );
+            assert_eq!(
+                reader
+                    .raw_account_block(account_block_offset + meta_size, stored_account.data_len())
+                    .unwrap(),
+                stored_account.data()
+            );
+        }
+    }
+
+    #[test]
+    fn test_owners_only_reader() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, _accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_owners_only_reader",
+            &[1, 2, 3, 4, 1000, 2000, 5, 0, 1, 2],
+        );
+        let owners = tiered_storage.reader().unwrap().owners_table().unwrap();
+
+        let owners_view =
+            TieredStorageReader::new_owners_only_from_path(tiered_storage.path()).unwrap();
+        assert_eq!(owners_view.len(), owners.len());
+        assert!(!owners_view.is_empty());
+        for (i, owner) in owners.iter().enumerate() {
+            assert_eq!(owners_view.get(i), Some(*owner));
+        }
+        assert_eq!(owners_view.get(owners.len()), None);
+
+        // non-tiered files are rejected
+        let path = temp_dir.path().join("test_owners_only_reader_invalid");
+        let mut bytes = fs::read(tiered_storage.path()).unwrap();
+        *bytes.last_mut().unwrap() ^= 0xFF;
+        fs::write(&path, &bytes).unwrap();
+        assert_matches!(
+            TieredStorageReader::new_owners_only_from_path(&path),
+            Err(TieredStorageError::MagicNumberMismatch(..))
+        );
+        fs::write(&path, [0; FOOTER_SIZE]).unwrap();
+        assert_matches!(
+            TieredStorageReader::new_owners_only_from_path(&path),
+            Err(TieredStorageError::InvalidFooterVersion(0))
+        );
+        fs::write(&path, [0xAB; 10]).unwrap();
+        assert_matches!(
+            TieredStorageReader::new_owners_only_from_path(&path),
+            Err(TieredStorageError::FileTooSmall(_, 10))
+        );
+    }
+
+    #[test]
+    fn test_new_writable_checked() {
+        let temp_dir = tempdir().unwrap();
+        let missing_dir = temp_dir.path().join("missing_dir");
+        assert_matches!(
+            TieredStorage::new_writable_checked(missing_dir.join("test_new_writable_checked")),
+            Err(TieredStorageError::ParentDirMissing(dir)) if dir == missing_dir
+        );
+
+        // a parent that is a file is not a directory either
+        let parent_file = temp_dir.path().join("parent_file");
+        fs::write(&parent_file, []).unwrap();
+        assert_matches!(
+            TieredStorage::new_writable_checked(parent_file.join("test_new_writable_checked")),
+            Err(TieredStorageError::ParentDirMissing(dir)) if dir == parent_file
+        );
+
+        // instances that are never written must not be dropped
+        let path = temp_dir.path().join("test_new_writable_checked");
+        let tiered_storage = ManuallyDrop::new(TieredStorage::new_writable_checked(&path).unwrap());
+        assert!(!tiered_storage.is_read_only());
+        assert_eq!(tiered_storage.path(), path);
+
+        // a bare file name lives in the current directory
+        let _tiered_storage = ManuallyDrop::new(
+            TieredStorage::new_writable_checked("test_new_writable_checked").unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_write_accounts_mixed_size() {
+        do_test_write_accounts(
+            "test_write_accounts_mixed_size",
+            &[
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 1000, 2000, 3000, 4000, 9, 8, 7, 6, 5, 4, 3, 2, 1,
+            ],
+            HOT_FORMAT.clone(),
+        );
+    }
+
+    #[test]
+    fn test_get_accounts() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, hashes) = write_test_accounts(
+            &temp_dir,
+            "test_get_accounts",
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 1000, 2000, 3000],
+        );
+        let reader = tiered_storage.reader().unwrap();
+
+        // mix present and absent addresses, in an order that differs from
+        // the order they were written in
+        let absent_addresses: Vec<_> = std::iter::repeat_with(Pubkey::new_unique).take(3).collect();
+        let expected = [
+            Some(7),
+            None,
+            Some(0),
+            Some(12),
+            None,
+            Some(3),
+            Some(7),
+            None,
+        ];
+        let mut absent_iter = absent_addresses.iter();
+        let addresses: Vec<_> = expected
+            .iter()
+            .map(|i| match i {
+                Some(i) => accounts[*i].0.pubkey,
+                None => *absent_iter.next().unwrap(),
+            })
+            .collect();
+
+        let stored_metas = reader.get_accounts(&addresses).unwrap();
+        assert_eq!(stored_metas.len(), addresses.len());
+        for (stored_meta, expected) in stored_metas.iter().zip(expected) {
+            match expected {
+                Some(i) => {
+                    let stored_meta = stored_meta.as_ref().unwrap();
+                    assert_eq!(*stored_meta.pubkey(), accounts[i].0.pubkey);
+                    verify_account(stored_meta, Some(&accounts[i].1), &hashes[i]);
+                }
+                None => assert!(stored_meta.is_none()),
+            }
+        }
+
+        assert!(reader.get_accounts(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_efficiency_report() {
+        let account_data_sizes = &[
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 1000, 2000, 3000, 4000, 9, 8, 7, 6, 5, 4, 3, 2, 1,
+        ];
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, _accounts, _hashes) =
+            write_test_accounts(&temp_dir, "test_efficiency_report", account_data_sizes);
+        let reader = tiered_storage.reader().unwrap();
+        let report = reader.efficiency_report().unwrap();
+
+        assert_eq!(
+            report.file_size as u64,
+            std::fs::metadata(tiered_storage.path()).unwrap().len()
+        );
+        assert_eq!(
+            report.data_bytes as u64,
+            account_data_sizes.iter().sum::<u64>()
+        );
+        assert_eq!(
+            report.overhead_bytes + report.data_bytes + report.slack_bytes,
+            report.file_size
+        );
+        assert!(report.slack_bytes > 0);
+        assert!(report.overhead_bytes >= FOOTER_SIZE);
+        assert_eq!(
+            report.overhead_ratio,
+            report.overhead_bytes as f64 / report.file_size as f64
+        );
+        assert!(report.overhead_ratio > 0.0 && report.overhead_ratio < 1.0);
+        assert_eq!(report.compression_ratio, 1.0);
+        // every test account has a unique owner
+        assert_eq!(report.dedup_savings_bytes, 0);
+    }
+
+    #[test]
+    fn test_stats() {
+        let temp_dir = tempdir().unwrap();
+
+        // all accounts owned by a single program
+        let program_id = Pubkey::new_unique();
+        let mut accounts: Vec<_> = (1..=20).map(create_account).collect();
+        for (_, account) in accounts.iter_mut() {
+            account.set_owner(program_id);
+        }
+        let (tiered_storage, _hashes) =
+            write_accounts_to(&temp_dir, "test_stats_single_owner", &accounts);
+        let stats = tiered_storage.reader().unwrap().stats();
+        assert_eq!(stats.num_accounts, 20);
+        assert_eq!(stats.num_unique_owners, 1);
+
+        // every four accounts share an owner
+        let owners: Vec<_> = std::iter::repeat_with(Pubkey::new_unique).take(5).collect();
+        for (i, (_, account)) in accounts.iter_mut().enumerate() {
+            account.set_owner(owners[i / 4]);
+        }
+        for (stored_meta, _) in accounts.iter_mut() {
+            stored_meta.pubkey = Pubkey::new_unique();
+        }
+        let (tiered_storage, _hashes) =
+            write_accounts_to(&temp_dir, "test_stats_shared_owners", &accounts);
+        let stats = tiered_storage.reader().unwrap().stats();
+        assert_eq!(stats.num_accounts, 20);
+        assert_eq!(stats.num_unique_owners, 5);
+    }
+
+    #[test]
+    fn test_debug_dump() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_debug_dump",
+            &[
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 1000, 2000, 3000, 4000, 9, 8, 7, 6, 5, 4, 3, 2, 1,
+            ],
+        );
+        let reader = tiered_storage.reader().unwrap();
+
+        let mut buffer = Vec::<u8>::new();
+        reader.debug_dump(&mut buffer).unwrap();
+        let dump = String::from_utf8(buffer).unwrap();
+
+        assert!(dump.contains(&format!("num_accounts: {}\n", accounts.len())));
+        assert!(dump.contains("account_meta_format: Hot\n"));
+        assert_eq!(
+            dump.lines()
+                .filter(|line| line.starts_with("account["))
+                .count(),
+            accounts.len()
+        );
+        for (stored_meta, account) in &accounts {
+            assert!(dump.contains(&format!(
+                "pubkey: {}, lamports: {}, data_len: {}, owner: {}",
+                stored_meta.pubkey,
+                account.lamports(),
+                account.data().len(),
+                account.owner(),
+            )));
+        }
+    }
+
+    #[test]
+    fn test_hash_chunk() {
+        let temp_dir = tempdir().unwrap();
+        let (tiered_storage, _accounts, _hashes) = write_test_accounts(
+            &temp_dir,
+            "test_hash_chunk",
+            &[
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 1000, 2000, 3000, 4000, 9, 8, 7,
+            ],
+        );
+        let reader = tiered_storage.reader().unwrap();
+        let num_accounts = reader.num_accounts();
+
+        let mut single_pass_state = HashState::default();
+        let next = reader
+            .hash_chunk(&mut single_pass_state, IndexOffset(0), num_accounts)
+            .unwrap();
+        assert_eq!(next.0 as usize, num_accounts);
+
+        for chunk_size in [1, 3, 4, num_accounts + 1] {
+            let mut chunked_state = HashState::default();
+            let mut index_offset = IndexOffset(0);
+            while (index_offset.0 as usize) < num_accounts {
+                index_offset = reader
+                    .hash_chunk(&mut chunked_state, index_offset, chunk_size)
+                    .unwrap();
+            }
+            assert_eq!(chunked_state.finalize(), single_pass_state.finalize());
+        }
+
+        // hashing past the end is a no-op
+        let mut state = HashState::default();
+        assert_eq!(
+            reader
+                .hash_chunk(&mut state, IndexOffset(num_accounts as u32), 10)
+                .unwrap(),
+            IndexOffset(num_accounts as u32)
+        );
+        assert_eq!(state.finalize(), HashState::default().finalize());
+        assert_ne!(single_pass_state.finalize(), state.finalize());
+    }
 }
@@ -1,5 +1,13 @@
-use {super::footer::SanitizeFooterError, std::path::PathBuf, thiserror::Error};
+use {
+    super::footer::{AccountBlockFormat, AccountMetaFormat, SanitizeFooterError},
+    solana_sdk::pubkey::Pubkey,
+    std::path::PathBuf,
+    thiserror::Error,
+};
 
+/// The errors of tiered storage.  The variants that wrap another error
+/// return it from `std::error::Error::source()`, and the variants about a
+/// specific file include its path when displayed.
 #[derive(Error, Debug)]
 pub enum TieredStorageError {
     #[error("I/O error: {0}")]
@@ -14,21 +22,54 @@ pub enum TieredStorageError {
     #[error("UnknownFormat: the tiered storage format is unknown for file {0}")]
     UnknownFormat(PathBuf),
 
+    #[error("ParentDirMissing: parent directory {0} does not exist")]
+    ParentDirMissing(PathBuf),
+
     #[error("Unsupported: the feature is not yet supported")]
     Unsupported(),
 
+    #[error("FileTooSmall: file {0} of {1} bytes is too small to be a tiered storage file")]
+    FileTooSmall(PathBuf, u64),
+
     #[error("invalid footer size: {0}, expected: {1}")]
     InvalidFooterSize(u64, u64),
 
     #[error("invalid footer version: {0}")]
     InvalidFooterVersion(u64),
 
+    #[error("UnsupportedFormatVersion: format version {0} is newer than the supported version")]
+    UnsupportedFormatVersion(u64),
+
     #[error("footer is unsanitary: {0}")]
     SanitizeFooter(#[from] SanitizeFooterError),
 
     #[error("OffsetOutOfBounds: offset {0} is larger than the supported size {1}")]
     OffsetOutOfBounds(usize, usize),
 
+    #[error("IndexOffsetOutOfBounds: index offset {0} exceeds the number of accounts {1}")]
+    IndexOffsetOutOfBounds(u32, u32),
+
+    #[error(
+        "AccountBlockRangeOutOfBounds: range of {1} bytes at {0} exceeds the account blocks of \
+         {2} bytes"
+    )]
+    AccountBlockRangeOutOfBounds(u64, u64, u64),
+
+    #[error("CorruptIndex: index entry {0} is invalid: {1}")]
+    CorruptIndex(u32, String),
+
     #[error("OffsetAlignmentError: offset {0} must be multiple of {1}")]
     OffsetAlignmentError(usize, usize),
+
+    #[error("InvalidMetaEntrySize: meta entry size {0} does not match the expected size {1}")]
+    InvalidMetaEntrySize(usize, usize),
+
+    #[error("IncompatibleBlockFormat: {0:?} account metas do not support {1:?} account blocks")]
+    IncompatibleBlockFormat(AccountMetaFormat, AccountBlockFormat),
+
+    #[error("DuplicatePubkey: account {0} is written more than once")]
+    DuplicatePubkey(Pubkey),
+
+    #[error("Unrecoverable: no intact account and index blocks were found in file {0}")]
+    Unrecoverable(PathBuf),
 }
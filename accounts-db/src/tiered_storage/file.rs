@@ -2,7 +2,7 @@ use {
     bytemuck::{AnyBitPattern, NoUninit},
     std::{
         fs::{File, OpenOptions},
-        io::{Read, Result as IoResult, Seek, SeekFrom, Write},
+        io::{self, Error as IoError, ErrorKind, Read, Result as IoResult, Seek, SeekFrom, Write},
         mem,
         path::Path,
     },
@@ -101,7 +101,29 @@ impl TieredStorageFile {
         Ok(bytes.len())
     }
 
+    /// Copies exactly `len` bytes from `reader` to the file in fixed-size
+    /// chunks, so that the data never needs to be held in memory at once.
+    ///
+    /// An error of kind UnexpectedEof is returned if `reader` runs out of
+    /// data before `len` bytes are copied.
+    pub fn write_from_reader(&self, reader: impl Read, len: usize) -> IoResult<usize> {
+        let copied = io::copy(&mut reader.take(len as u64), &mut &self.0)?;
+        if copied != len as u64 {
+            return Err(IoError::new(
+                ErrorKind::UnexpectedEof,
+                format!("expected {len} bytes from the reader, but only got {copied}"),
+            ));
+        }
+
+        Ok(len)
+    }
+
     pub fn read_bytes(&self, buffer: &mut [u8]) -> IoResult<()> {
         (&self.0).read_exact(buffer)
     }
+
+    /// Flushes all the written data and metadata of the file to disk.
+    pub fn sync_all(&self) -> IoResult<()> {
+        self.0.sync_all()
+    }
 }
@@ -10,17 +10,25 @@ use {
     bytemuck::{Pod, Zeroable},
     memmap2::Mmap,
     num_enum::TryFromPrimitiveError,
-    solana_sdk::{hash::Hash, pubkey::Pubkey},
+    solana_sdk::{clock::Slot, hash::Hash, pubkey::Pubkey},
     std::{mem, path::Path},
     thiserror::Error,
 };
 
-pub const FOOTER_FORMAT_VERSION: u64 = 1;
+/// The format version written by this writer.  Version 2 added the slot to
+/// the footer, and files with the version 1 footer remain readable.
+pub const FOOTER_FORMAT_VERSION: u64 = 2;
 
 /// The size of the footer struct + the magic number at the end.
 pub const FOOTER_SIZE: usize =
     mem::size_of::<TieredStorageFooter>() + mem::size_of::<TieredStorageMagicNumber>();
-static_assertions::const_assert_eq!(mem::size_of::<TieredStorageFooter>(), 160);
+static_assertions::const_assert_eq!(mem::size_of::<TieredStorageFooter>(), 168);
+
+/// The size of the version 1 footer, which has no slot, + the magic number.
+/// This is also the smallest footer a readable file can end with.
+pub const FOOTER_SIZE_V1: usize =
+    mem::size_of::<TieredStorageFooterV1>() + mem::size_of::<TieredStorageMagicNumber>();
+static_assertions::const_assert_eq!(mem::size_of::<TieredStorageFooterV1>(), 160);
 
 /// The size of the ending part of the footer.  This size should remain unchanged
 /// even when the footer's format changes.
@@ -54,6 +62,7 @@ impl Default for TieredStorageMagicNumber {
     num_enum::IntoPrimitive,
     num_enum::TryFromPrimitive,
 )]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AccountMetaFormat {
     #[default]
     Hot = 0,
@@ -73,6 +82,7 @@ pub enum AccountMetaFormat {
     num_enum::IntoPrimitive,
     num_enum::TryFromPrimitive,
 )]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AccountBlockFormat {
     #[default]
     AlignedRaw = 0,
@@ -81,6 +91,7 @@ pub enum AccountBlockFormat {
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TieredStorageFooter {
     // formats
     /// The format of the account meta entry.
@@ -126,6 +137,10 @@ pub struct TieredStorageFooter {
     /// A hash that represents a tiered accounts file for consistency check.
     pub hash: Hash,
 
+    /// The slot that the accounts of this file were written for.  Footers of
+    /// format version 1 have no slot, which is then read as 0.
+    pub slot: Slot,
+
     /// The format version of the tiered accounts file.
     pub format_version: u64,
     // The below fields belong to footer tail.
@@ -156,11 +171,83 @@ const _: () = assert!(
          + std::mem::size_of::<Pubkey>() // min_account_address
          + std::mem::size_of::<Pubkey>() // max_account_address
          + std::mem::size_of::<Hash>() // hash
+         + std::mem::size_of::<Slot>() // slot
          + std::mem::size_of::<u64>() // format_version
          + std::mem::size_of::<u64>(), // footer_size
     "TieredStorageFooter cannot have any padding"
 );
 
+/// The footer of format version 1, which is TieredStorageFooter without the
+/// slot.  It is only read, and is converted to a TieredStorageFooter with
+/// the slot defaulting to 0.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+struct TieredStorageFooterV1 {
+    account_meta_format: AccountMetaFormat,
+    owners_block_format: OwnersBlockFormat,
+    index_block_format: IndexBlockFormat,
+    account_block_format: AccountBlockFormat,
+    account_entry_count: u32,
+    account_meta_entry_size: u32,
+    account_block_size: u64,
+    owner_count: u32,
+    owner_entry_size: u32,
+    index_block_offset: u64,
+    owners_block_offset: u64,
+    min_account_address: Pubkey,
+    max_account_address: Pubkey,
+    hash: Hash,
+    format_version: u64,
+    footer_size: u64,
+}
+
+impl From<TieredStorageFooterV1> for TieredStorageFooter {
+    fn from(footer: TieredStorageFooterV1) -> Self {
+        Self {
+            account_meta_format: footer.account_meta_format,
+            owners_block_format: footer.owners_block_format,
+            index_block_format: footer.index_block_format,
+            account_block_format: footer.account_block_format,
+            account_entry_count: footer.account_entry_count,
+            account_meta_entry_size: footer.account_meta_entry_size,
+            account_block_size: footer.account_block_size,
+            owner_count: footer.owner_count,
+            owner_entry_size: footer.owner_entry_size,
+            index_block_offset: footer.index_block_offset,
+            owners_block_offset: footer.owners_block_offset,
+            min_account_address: footer.min_account_address,
+            max_account_address: footer.max_account_address,
+            hash: footer.hash,
+            slot: Slot::default(),
+            format_version: footer.format_version,
+            footer_size: footer.footer_size,
+        }
+    }
+}
+
+impl From<TieredStorageFooter> for TieredStorageFooterV1 {
+    fn from(footer: TieredStorageFooter) -> Self {
+        Self {
+            account_meta_format: footer.account_meta_format,
+            owners_block_format: footer.owners_block_format,
+            index_block_format: footer.index_block_format,
+            account_block_format: footer.account_block_format,
+            account_entry_count: footer.account_entry_count,
+            account_meta_entry_size: footer.account_meta_entry_size,
+            account_block_size: footer.account_block_size,
+            owner_count: footer.owner_count,
+            owner_entry_size: footer.owner_entry_size,
+            index_block_offset: footer.index_block_offset,
+            owners_block_offset: footer.owners_block_offset,
+            min_account_address: footer.min_account_address,
+            max_account_address: footer.max_account_address,
+            hash: footer.hash,
+            format_version: footer.format_version,
+            footer_size: footer.footer_size,
+        }
+    }
+}
+
 impl Default for TieredStorageFooter {
     fn default() -> Self {
         Self {
@@ -178,6 +265,7 @@ impl Default for TieredStorageFooter {
             hash: Hash::new_unique(),
             min_account_address: Pubkey::default(),
             max_account_address: Pubkey::default(),
+            slot: Slot::default(),
             format_version: FOOTER_FORMAT_VERSION,
             footer_size: FOOTER_SIZE as u64,
         }
@@ -185,6 +273,25 @@ impl Default for TieredStorageFooter {
 }
 
 impl TieredStorageFooter {
+    /// Returns the offset pointing to the first byte of the account blocks,
+    /// which is always 0 as the account blocks start the file.
+    pub fn account_blocks_offset(&self) -> u64 {
+        0
+    }
+
+    /// Returns true if the index block is sorted by address, which allows
+    /// readers to binary-search it.
+    pub fn index_sorted(&self) -> bool {
+        self.index_block_format.is_sorted()
+    }
+
+    /// Returns the total size of the account blocks, which span from
+    /// account_blocks_offset() up to the index block.
+    pub fn account_blocks_size(&self) -> u64 {
+        self.index_block_offset
+            .saturating_sub(self.account_blocks_offset())
+    }
+
     pub fn new_from_path(path: impl AsRef<Path>) -> TieredStorageResult<Self> {
         let file = TieredStorageFile::new_readonly(path);
         Self::new_from_footer_block(&file)
@@ -198,23 +305,35 @@ impl TieredStorageFooter {
         Ok(())
     }
 
+    /// Writes the footer in the layout of format version 1, which drops the
+    /// slot, so that tests can produce files of older writers.
+    #[cfg(test)]
+    pub(crate) fn write_v1_footer_block(
+        &self,
+        file: &TieredStorageFile,
+    ) -> TieredStorageResult<()> {
+        let footer = TieredStorageFooterV1 {
+            format_version: 1,
+            footer_size: FOOTER_SIZE_V1 as u64,
+            ..TieredStorageFooterV1::from(*self)
+        };
+        // SAFETY: The footer does not contain any uninitialized bytes.
+        unsafe { file.write_type(&footer)? };
+        file.write_pod(&TieredStorageMagicNumber::default())?;
+
+        Ok(())
+    }
+
     pub fn new_from_footer_block(file: &TieredStorageFile) -> TieredStorageResult<Self> {
         file.seek_from_end(-(FOOTER_TAIL_SIZE as i64))?;
 
         let mut footer_version: u64 = 0;
         file.read_pod(&mut footer_version)?;
-        if footer_version != FOOTER_FORMAT_VERSION {
-            return Err(TieredStorageError::InvalidFooterVersion(footer_version));
-        }
+        Self::check_format_version(footer_version)?;
 
         let mut footer_size: u64 = 0;
         file.read_pod(&mut footer_size)?;
-        if footer_size != FOOTER_SIZE as u64 {
-            return Err(TieredStorageError::InvalidFooterSize(
-                footer_size,
-                FOOTER_SIZE as u64,
-            ));
-        }
+        Self::check_footer_size(footer_version, footer_size)?;
 
         let mut magic_number = TieredStorageMagicNumber::zeroed();
         file.read_pod(&mut magic_number)?;
@@ -225,31 +344,35 @@ impl TieredStorageFooter {
             ));
         }
 
-        let mut footer = Self::default();
         file.seek_from_end(-(footer_size as i64))?;
-        // SAFETY: We sanitize the footer to ensure all the bytes are
-        // actually safe to interpret as a TieredStorageFooter.
-        unsafe { file.read_type(&mut footer)? };
+        let footer = if footer_version == 1 {
+            let mut footer_v1 = TieredStorageFooterV1::from(Self::default());
+            // SAFETY: We sanitize the footer to ensure all the bytes are
+            // actually safe to interpret as a TieredStorageFooter.
+            unsafe { file.read_type(&mut footer_v1)? };
+            Self::from(footer_v1)
+        } else {
+            let mut footer = Self::default();
+            // SAFETY: We sanitize the footer to ensure all the bytes are
+            // actually safe to interpret as a TieredStorageFooter.
+            unsafe { file.read_type(&mut footer)? };
+            footer
+        };
         Self::sanitize(&footer)?;
 
         Ok(footer)
     }
 
-    pub fn new_from_mmap(mmap: &Mmap) -> TieredStorageResult<&TieredStorageFooter> {
+    /// Returns a copy of the footer at the end of `mmap`, as footers of older
+    /// format versions are converted to the current layout.
+    pub fn new_from_mmap(mmap: &Mmap) -> TieredStorageResult<TieredStorageFooter> {
         let offset = mmap.len().saturating_sub(FOOTER_TAIL_SIZE);
 
-        let (footer_version, offset) = get_pod::<u64>(mmap, offset)?;
-        if *footer_version != FOOTER_FORMAT_VERSION {
-            return Err(TieredStorageError::InvalidFooterVersion(*footer_version));
-        }
+        let (&footer_version, offset) = get_pod::<u64>(mmap, offset)?;
+        Self::check_format_version(footer_version)?;
 
         let (&footer_size, offset) = get_pod::<u64>(mmap, offset)?;
-        if footer_size != FOOTER_SIZE as u64 {
-            return Err(TieredStorageError::InvalidFooterSize(
-                footer_size,
-                FOOTER_SIZE as u64,
-            ));
-        }
+        Self::check_footer_size(footer_version, footer_size)?;
 
         let (magic_number, _offset) = get_pod::<TieredStorageMagicNumber>(mmap, offset)?;
         if *magic_number != TieredStorageMagicNumber::default() {
@@ -262,12 +385,52 @@ impl TieredStorageFooter {
         let footer_offset = mmap.len().saturating_sub(footer_size as usize);
         // SAFETY: We sanitize the footer to ensure all the bytes are
         // actually safe to interpret as a TieredStorageFooter.
-        let (footer, _offset) = unsafe { get_type::<TieredStorageFooter>(mmap, footer_offset)? };
-        Self::sanitize(footer)?;
+        let footer = if footer_version == 1 {
+            let (footer_v1, _offset) =
+                unsafe { get_type::<TieredStorageFooterV1>(mmap, footer_offset)? };
+            Self::from(*footer_v1)
+        } else {
+            let (footer, _offset) =
+                unsafe { get_type::<TieredStorageFooter>(mmap, footer_offset)? };
+            *footer
+        };
+        Self::sanitize(&footer)?;
 
         Ok(footer)
     }
 
+    /// Returns Ok(()) if `footer_size` is the size of the footer of the
+    /// specified format version, which must already have been checked.
+    fn check_footer_size(format_version: u64, footer_size: u64) -> TieredStorageResult<()> {
+        let expected_size = match format_version {
+            1 => FOOTER_SIZE_V1,
+            _ => FOOTER_SIZE,
+        } as u64;
+        if footer_size != expected_size {
+            return Err(TieredStorageError::InvalidFooterSize(
+                footer_size,
+                expected_size,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns Ok(()) if the reader supports the specified format version.
+    ///
+    /// Versions newer than FOOTER_FORMAT_VERSION come from incompatible
+    /// future writers and are reported as UnsupportedFormatVersion, while
+    /// the others this reader does not know are reported as
+    /// InvalidFooterVersion.
+    fn check_format_version(format_version: u64) -> TieredStorageResult<()> {
+        match format_version {
+            1..=FOOTER_FORMAT_VERSION => Ok(()),
+            version if version > FOOTER_FORMAT_VERSION => {
+                Err(TieredStorageError::UnsupportedFormatVersion(version))
+            }
+            version => Err(TieredStorageError::InvalidFooterVersion(version)),
+        }
+    }
+
     /// Sanitizes the footer
     ///
     /// Since the various formats only have specific valid values, they must be sanitized
@@ -328,6 +491,7 @@ mod tests {
         crate::{
             append_vec::test_utils::get_append_vec_path, tiered_storage::file::TieredStorageFile,
         },
+        assert_matches::assert_matches,
         memoffset::offset_of,
         solana_sdk::hash::Hash,
     };
@@ -350,6 +514,7 @@ mod tests {
             hash: Hash::new_unique(),
             min_account_address: Pubkey::default(),
             max_account_address: Pubkey::new_unique(),
+            slot: 1234,
             format_version: FOOTER_FORMAT_VERSION,
             footer_size: FOOTER_SIZE as u64,
         };
@@ -368,6 +533,129 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_footer_serde() {
+        let path = get_append_vec_path("test_footer_serde");
+        let expected_footer = TieredStorageFooter {
+            account_entry_count: 300,
+            account_meta_entry_size: 24,
+            account_block_size: 4096,
+            owner_count: 250,
+            owner_entry_size: 32,
+            index_block_offset: 1069600,
+            owners_block_offset: 1081200,
+            min_account_address: Pubkey::new_unique(),
+            max_account_address: Pubkey::new_unique(),
+            slot: 1234,
+            ..TieredStorageFooter::default()
+        };
+        {
+            let file = TieredStorageFile::new_writable(&path.path).unwrap();
+            expected_footer.write_footer_block(&file).unwrap();
+        }
+
+        let footer = TieredStorageFooter::new_from_path(&path.path).unwrap();
+        let json = serde_json::to_string(&footer).unwrap();
+        let deserialized: TieredStorageFooter = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, expected_footer);
+    }
+
+    #[test]
+    fn test_footer_v1() {
+        let path = get_append_vec_path("test_footer_v1");
+        let footer = TieredStorageFooter {
+            account_entry_count: 300,
+            owner_count: 250,
+            index_block_offset: 1069600,
+            owners_block_offset: 1081200,
+            max_account_address: Pubkey::new_unique(),
+            slot: 1234,
+            ..TieredStorageFooter::default()
+        };
+        {
+            let file = TieredStorageFile::new_writable(&path.path).unwrap();
+            footer.write_v1_footer_block(&file).unwrap();
+        }
+        assert_eq!(
+            std::fs::metadata(&path.path).unwrap().len(),
+            FOOTER_SIZE_V1 as u64
+        );
+
+        // the slot is not persisted in version 1, and reads as 0
+        let expected_footer = TieredStorageFooter {
+            slot: 0,
+            format_version: 1,
+            footer_size: FOOTER_SIZE_V1 as u64,
+            ..footer
+        };
+        assert_eq!(
+            TieredStorageFooter::new_from_path(&path.path).unwrap(),
+            expected_footer
+        );
+        let file = std::fs::File::open(&path.path).unwrap();
+        let mmap = unsafe { memmap2::MmapOptions::new().map(&file).unwrap() };
+        assert_eq!(
+            TieredStorageFooter::new_from_mmap(&mmap).unwrap(),
+            expected_footer
+        );
+    }
+
+    #[test]
+    fn test_footer_size_mismatch() {
+        // a version 1 footer must not claim the size of the current footer
+        assert_matches!(
+            TieredStorageFooter::check_footer_size(1, FOOTER_SIZE as u64),
+            Err(TieredStorageError::InvalidFooterSize(size, expected))
+                if size == FOOTER_SIZE as u64 && expected == FOOTER_SIZE_V1 as u64
+        );
+        assert_matches!(
+            TieredStorageFooter::check_footer_size(FOOTER_FORMAT_VERSION, FOOTER_SIZE_V1 as u64),
+            Err(TieredStorageError::InvalidFooterSize(size, expected))
+                if size == FOOTER_SIZE_V1 as u64 && expected == FOOTER_SIZE as u64
+        );
+    }
+
+    #[test]
+    fn test_footer_unsupported_format_version() {
+        let path = get_append_vec_path("test_footer_unsupported_format_version");
+        let footer = TieredStorageFooter {
+            format_version: FOOTER_FORMAT_VERSION + 1,
+            ..TieredStorageFooter::default()
+        };
+        {
+            let file = TieredStorageFile::new_writable(&path.path).unwrap();
+            footer.write_footer_block(&file).unwrap();
+        }
+
+        assert_matches!(
+            TieredStorageFooter::new_from_path(&path.path),
+            Err(TieredStorageError::UnsupportedFormatVersion(version))
+                if version == FOOTER_FORMAT_VERSION + 1
+        );
+        let file = std::fs::File::open(&path.path).unwrap();
+        let mmap = unsafe { memmap2::MmapOptions::new().map(&file).unwrap() };
+        assert_matches!(
+            TieredStorageFooter::new_from_mmap(&mmap),
+            Err(TieredStorageError::UnsupportedFormatVersion(version))
+                if version == FOOTER_FORMAT_VERSION + 1
+        );
+    }
+
+    #[test]
+    fn test_check_format_version() {
+        assert!(TieredStorageFooter::check_format_version(1).is_ok());
+        assert!(TieredStorageFooter::check_format_version(FOOTER_FORMAT_VERSION).is_ok());
+        assert_matches!(
+            TieredStorageFooter::check_format_version(0),
+            Err(TieredStorageError::InvalidFooterVersion(0))
+        );
+        assert_matches!(
+            TieredStorageFooter::check_format_version(u64::MAX),
+            Err(TieredStorageError::UnsupportedFormatVersion(u64::MAX))
+        );
+    }
+
     #[test]
     fn test_footer_layout() {
         assert_eq!(offset_of!(TieredStorageFooter, account_meta_format), 0x00);
@@ -387,8 +675,9 @@ mod tests {
         assert_eq!(offset_of!(TieredStorageFooter, min_account_address), 0x30);
         assert_eq!(offset_of!(TieredStorageFooter, max_account_address), 0x50);
         assert_eq!(offset_of!(TieredStorageFooter, hash), 0x70);
-        assert_eq!(offset_of!(TieredStorageFooter, format_version), 0x90);
-        assert_eq!(offset_of!(TieredStorageFooter, footer_size), 0x98);
+        assert_eq!(offset_of!(TieredStorageFooter, slot), 0x90);
+        assert_eq!(offset_of!(TieredStorageFooter, format_version), 0x98);
+        assert_eq!(offset_of!(TieredStorageFooter, footer_size), 0xA0);
     }
 
     #[test]
@@ -2,7 +2,7 @@
 
 use {
     crate::{
-        account_storage::meta::{StoredAccountInfo, StoredAccountMeta},
+        account_storage::meta::{StoredAccountInfo, StoredAccountMeta, StoredMetaWriteVersion},
         accounts_file::MatchAccountOwnerError,
         accounts_hash::AccountHash,
         rent_collector::RENT_EXEMPT_RENT_EPOCH,
@@ -11,19 +11,33 @@ use {
             file::TieredStorageFile,
             footer::{AccountBlockFormat, AccountMetaFormat, TieredStorageFooter},
             index::{AccountIndexWriterEntry, AccountOffset, IndexBlockFormat, IndexOffset},
+            lookup_cache::LookupCache,
             meta::{AccountMetaFlags, AccountMetaOptionalFields, TieredAccountMeta},
-            mmap_utils::{get_pod, get_slice},
+            mmap_utils::{get_pod, get_slice, prefetch},
             owners::{OwnerOffset, OwnersBlockFormat, OwnersTable, OWNER_NO_OWNER},
-            readable::TieredReadableAccount,
+            readable::{EfficiencyReport, TieredReadableAccount},
             StorableAccounts, StorableAccountsWithHashesAndWriteVersions, TieredStorageError,
-            TieredStorageFormat, TieredStorageResult,
+            TieredStorageFormat, TieredStorageResult, TOTAL_MAPPED_BYTES,
         },
     },
     bytemuck::{Pod, Zeroable},
     memmap2::{Mmap, MmapOptions},
     modular_bitfield::prelude::*,
-    solana_sdk::{account::ReadableAccount, pubkey::Pubkey, stake_history::Epoch},
-    std::{borrow::Borrow, fs::OpenOptions, option::Option, path::Path},
+    solana_sdk::{
+        account::ReadableAccount, clock::Slot, hash::Hash, pubkey::Pubkey, stake_history::Epoch,
+    },
+    std::{
+        borrow::Borrow,
+        collections::{HashMap, HashSet},
+        fs::{File, OpenOptions},
+        io::Read,
+        option::Option,
+        path::Path,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    },
 };
 
 pub const HOT_FORMAT: TieredStorageFormat = TieredStorageFormat {
@@ -36,7 +50,7 @@ pub const HOT_FORMAT: TieredStorageFormat = TieredStorageFormat {
 
 /// An helper function that creates a new default footer for hot
 /// accounts storage.
-fn new_hot_footer() -> TieredStorageFooter {
+pub(super) fn new_hot_footer() -> TieredStorageFooter {
     TieredStorageFooter {
         account_meta_format: HOT_FORMAT.account_meta_format,
         account_meta_entry_size: HOT_FORMAT.meta_entry_size as u32,
@@ -278,22 +292,195 @@ impl TieredAccountMeta for HotAccountMeta {
 /// The reader to a hot accounts file.
 #[derive(Debug)]
 pub struct HotStorageReader {
-    mmap: Mmap,
+    mmap: Arc<Mmap>,
     footer: TieredStorageFooter,
+    /// The optional cache of addresses resolved by find_index_offset().
+    lookup_cache: Option<LookupCache>,
+    /// The sorted offsets of all the account metas, which are only needed
+    /// to derive the account block sizes when the index is sorted by
+    /// address, as the account blocks are then in a different order.
+    sorted_account_offsets: Option<Vec<usize>>,
+    /// Whether each index entry has a corrupt account offset, and whether
+    /// it is skipped, either for being corrupt or for preceding a corrupt
+    /// entry.  Both are empty unless the reader was created
+    /// with_corrupt_entries_skipped().
+    corrupt_entries: Vec<bool>,
+    skipped_entries: Vec<bool>,
+    /// The number of addresses compared by find_index_offset() so far.
+    #[cfg(test)]
+    index_probes: std::sync::atomic::AtomicU64,
+}
+
+impl Drop for HotStorageReader {
+    fn drop(&mut self) {
+        TOTAL_MAPPED_BYTES.fetch_sub(self.mmap.len() as u64, Ordering::Relaxed);
+    }
 }
 
 impl HotStorageReader {
     /// Constructs a HotStorageReader from the specified path.
     pub fn new_from_path(path: impl AsRef<Path>) -> TieredStorageResult<Self> {
         let file = OpenOptions::new().read(true).open(path)?;
+        Self::new_from_file(file)
+    }
+
+    /// Constructs a HotStorageReader by memory-mapping an already-opened
+    /// hot storage file.
+    pub fn new_from_file(file: File) -> TieredStorageResult<Self> {
         let mmap = unsafe { MmapOptions::new().map(&file)? };
         // Here we are copying the footer, as accessing any data in a
         // TieredStorage instance requires accessing its Footer.
         // This can help improve cache locality and reduce the overhead
         // of indirection associated with memory-mapped accesses.
-        let footer = *TieredStorageFooter::new_from_mmap(&mmap)?;
+        let footer = TieredStorageFooter::new_from_mmap(&mmap)?;
+
+        TOTAL_MAPPED_BYTES.fetch_add(mmap.len() as u64, Ordering::Relaxed);
+        let mut reader = Self {
+            mmap: Arc::new(mmap),
+            footer,
+            lookup_cache: None,
+            sorted_account_offsets: None,
+            corrupt_entries: Vec::new(),
+            skipped_entries: Vec::new(),
+            #[cfg(test)]
+            index_probes: std::sync::atomic::AtomicU64::default(),
+        };
+        if footer.index_sorted() {
+            let mut offsets = (0..footer.account_entry_count)
+                .map(|i| Ok(reader.get_account_offset(IndexOffset(i))?.offset()))
+                .collect::<TieredStorageResult<Vec<_>>>()?;
+            offsets.sort_unstable();
+            reader.sorted_account_offsets = Some(offsets);
+        }
+        Ok(reader)
+    }
+
+    /// Enables caching of up to `capacity` addresses resolved by
+    /// find_index_offset().
+    pub fn with_lookup_cache(mut self, capacity: usize) -> Self {
+        self.lookup_cache = Some(LookupCache::new(capacity));
+        self
+    }
+
+    /// Checks every index entry and skips the corrupt ones from then on, so
+    /// that as many accounts as possible can be salvaged from a partially
+    /// corrupt file.  Returns the reader together with one
+    /// TieredStorageError::CorruptIndex for each corrupt entry.
+    ///
+    /// An entry is corrupt if its account offset is out of the account
+    /// blocks or, unless the index is sorted by address, does not follow
+    /// the offset of the previous valid entry.  As the end of an account
+    /// block is the offset of the next entry, the previous valid entry is
+    /// skipped as well.  With an index sorted by address, the account
+    /// block preceding that of a corrupt entry cannot be identified, so
+    /// its data may include the bytes of the skipped account.
+    ///
+    /// get_account(), and hence accounts(), moves on to the next entry that
+    /// is not skipped, and find_index_offset() does not find the addresses
+    /// of skipped entries.  The other methods that go over all the accounts,
+    /// such as get_accounts(), accounts_by_owner(), accounts_not_owned_by(),
+    /// and efficiency_report(), leave out skipped entries as well.
+    pub fn with_corrupt_entries_skipped(
+        mut self,
+    ) -> TieredStorageResult<(Self, Vec<TieredStorageError>)> {
+        let index_sorted = self.footer.index_sorted();
+        let mut corrupt_entries = vec![false; self.footer.account_entry_count as usize];
+        let mut skipped_entries = corrupt_entries.clone();
+        let mut errors = vec![];
+        let mut valid_offsets = Vec::with_capacity(corrupt_entries.len());
+        let mut prev_valid_entry = None;
+        for i in 0..self.footer.account_entry_count {
+            let account_offset = self.get_account_offset(IndexOffset(i))?;
+            let offset = account_offset.offset();
+            let error = match self.check_account_offset(IndexOffset(i), account_offset) {
+                Err(err) => Some(err),
+                Ok(()) => prev_valid_entry
+                    .filter(|(_, prev_offset)| !index_sorted && offset <= *prev_offset)
+                    .map(|(_, prev_offset)| {
+                        TieredStorageError::CorruptIndex(
+                            i,
+                            format!(
+                                "account offset {offset} does not follow the previous offset \
+                                 {prev_offset}"
+                            ),
+                        )
+                    }),
+            };
+            match error {
+                Some(error) => {
+                    corrupt_entries[i as usize] = true;
+                    skipped_entries[i as usize] = true;
+                    if let Some((prev_entry, _)) = prev_valid_entry.filter(|_| !index_sorted) {
+                        skipped_entries[prev_entry as usize] = true;
+                    }
+                    errors.push(error);
+                }
+                None => {
+                    valid_offsets.push(offset);
+                    prev_valid_entry = Some((i, offset));
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            if let Some(offsets) = &mut self.sorted_account_offsets {
+                valid_offsets.sort_unstable();
+                *offsets = valid_offsets;
+            }
+            self.corrupt_entries = corrupt_entries;
+            self.skipped_entries = skipped_entries;
+        }
+        Ok((self, errors))
+    }
+
+    /// Returns the first index offset, starting from `index_offset`, whose
+    /// account offset is not corrupt.
+    fn first_valid_entry(&self, mut index_offset: IndexOffset) -> IndexOffset {
+        while self
+            .corrupt_entries
+            .get(index_offset.0 as usize)
+            .copied()
+            .unwrap_or(false)
+        {
+            index_offset.0 += 1;
+        }
+        index_offset
+    }
+
+    /// Returns whether the specified index entry is skipped by
+    /// with_corrupt_entries_skipped().
+    fn is_skipped_entry(&self, index_offset: IndexOffset) -> bool {
+        self.skipped_entries
+            .get(index_offset.0 as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Returns the first index offset, starting from `index_offset`, that is
+    /// not skipped by with_corrupt_entries_skipped().
+    fn first_unskipped_entry(&self, mut index_offset: IndexOffset) -> IndexOffset {
+        while self.is_skipped_entry(index_offset) {
+            index_offset.0 += 1;
+        }
+        index_offset
+    }
 
-        Ok(Self { mmap, footer })
+    /// Returns an iterator over the index offsets of all the entries that
+    /// are not skipped by with_corrupt_entries_skipped().
+    fn unskipped_entries(&self) -> impl Iterator<Item = IndexOffset> + '_ {
+        (0..self.footer.account_entry_count)
+            .map(IndexOffset)
+            .filter(|index_offset| !self.is_skipped_entry(*index_offset))
+    }
+
+    /// Returns the lookup cache, if enabled.
+    pub fn lookup_cache(&self) -> Option<&LookupCache> {
+        self.lookup_cache.as_ref()
+    }
+
+    /// Returns the size of the memory-mapped region of the underlying file.
+    pub fn mapped_len(&self) -> u64 {
+        self.mmap.len() as u64
     }
 
     /// Returns the footer of the underlying tiered-storage accounts file.
@@ -301,30 +488,89 @@ impl HotStorageReader {
         &self.footer
     }
 
+    /// Returns the slot that the underlying tiered-storage accounts file
+    /// was written for.
+    pub fn slot(&self) -> Slot {
+        self.footer.slot
+    }
+
     /// Returns the number of files inside the underlying tiered-storage
     /// accounts file.
     pub fn num_accounts(&self) -> usize {
         self.footer.account_entry_count as usize
     }
 
+    /// Returns the number of unique owners inside the underlying
+    /// tiered-storage accounts file.
+    pub fn num_owners(&self) -> usize {
+        self.footer.owner_count as usize
+    }
+
+    /// Returns the addresses of all the unique owners in the order of their
+    /// OwnerOffset.
+    pub fn owners_table(&self) -> TieredStorageResult<Vec<Pubkey>> {
+        (0..self.footer.owner_count)
+            .map(|i| self.get_owner_address(OwnerOffset(i)).copied())
+            .collect()
+    }
+
     /// Returns the account meta located at the specified offset.
+    ///
+    /// TieredStorageError::OffsetOutOfBounds is returned if reading the
+    /// account meta would exceed the account blocks.
     fn get_account_meta_from_offset(
         &self,
         account_offset: HotAccountOffset,
     ) -> TieredStorageResult<&HotAccountMeta> {
         let offset = account_offset.offset();
-
-        assert!(
-            offset.saturating_add(std::mem::size_of::<HotAccountMeta>())
-                <= self.footer.index_block_offset as usize,
-            "reading HotAccountOffset ({}) would exceed accounts blocks offset boundary ({}).",
-            offset,
-            self.footer.index_block_offset,
-        );
+        let account_blocks_end = self.footer.index_block_offset as usize;
+        if offset.saturating_add(std::mem::size_of::<HotAccountMeta>()) > account_blocks_end {
+            return Err(TieredStorageError::OffsetOutOfBounds(
+                offset,
+                account_blocks_end,
+            ));
+        }
         let (meta, _) = get_pod::<HotAccountMeta>(&self.mmap, offset)?;
         Ok(meta)
     }
 
+    /// Returns the account offset and the account meta of the index entry
+    /// at the specified index offset.
+    ///
+    /// TieredStorageError::CorruptIndex is returned if the account offset
+    /// of the entry leaves no room for its account meta.
+    fn get_account_meta(
+        &self,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<(HotAccountOffset, &HotAccountMeta)> {
+        let account_offset = self.get_account_offset(index_offset)?;
+        self.check_account_offset(index_offset, account_offset)?;
+        let (meta, _) = get_pod::<HotAccountMeta>(&self.mmap, account_offset.offset())?;
+        Ok((account_offset, meta))
+    }
+
+    /// Returns TieredStorageError::CorruptIndex if `account_offset`, the
+    /// account offset of the index entry at `index_offset`, leaves no room
+    /// for an account meta within the account blocks.
+    fn check_account_offset(
+        &self,
+        index_offset: IndexOffset,
+        account_offset: HotAccountOffset,
+    ) -> TieredStorageResult<()> {
+        let offset = account_offset.offset();
+        let account_blocks_end = self.footer.index_block_offset as usize;
+        if offset.saturating_add(std::mem::size_of::<HotAccountMeta>()) > account_blocks_end {
+            return Err(TieredStorageError::CorruptIndex(
+                index_offset.0,
+                format!(
+                    "account offset {offset} exceeds the account blocks of {account_blocks_end} \
+                     bytes"
+                ),
+            ));
+        }
+        Ok(())
+    }
+
     /// Returns the offset to the account given the specified index.
     pub(super) fn get_account_offset(
         &self,
@@ -342,6 +588,195 @@ impl HotStorageReader {
             .get_account_address(&self.mmap, &self.footer, index)
     }
 
+    /// Returns the raw bytes of the `len` bytes at `start` of the account
+    /// blocks, without interpreting them.
+    ///
+    /// TieredStorageError::AccountBlockRangeOutOfBounds is returned if the
+    /// range does not lie within the account blocks.
+    pub fn raw_account_block(&self, start: u64, len: u64) -> TieredStorageResult<&[u8]> {
+        let account_blocks_size = self.footer.account_blocks_size();
+        if start
+            .checked_add(len)
+            .map_or(true, |end| end > account_blocks_size)
+        {
+            return Err(TieredStorageError::AccountBlockRangeOutOfBounds(
+                start,
+                len,
+                account_blocks_size,
+            ));
+        }
+
+        let (data, _) = get_slice(
+            &self.mmap,
+            (self.footer.account_blocks_offset() + start) as usize,
+            len as usize,
+        )?;
+        Ok(data)
+    }
+
+    /// Returns true if the account of the specified address is stored in
+    /// this file.  Only the index block is consulted.
+    pub fn contains(&self, address: &Pubkey) -> TieredStorageResult<bool> {
+        Ok(self.find_index_offset(address)?.is_some())
+    }
+
+    /// Returns the IndexOffset of the account of the specified address, or
+    /// None if it is not stored in this file.  Only the index block is
+    /// consulted, with a binary search if it is sorted by address, and
+    /// resolved addresses are cached when the lookup cache is enabled.
+    pub fn find_index_offset(&self, address: &Pubkey) -> TieredStorageResult<Option<IndexOffset>> {
+        if let Some(index_offset) = self
+            .lookup_cache
+            .as_ref()
+            .and_then(|cache| cache.get(address))
+        {
+            return Ok(Some(index_offset));
+        }
+
+        let mut probes = 0;
+        let index_offset = if self.footer.index_sorted() {
+            self.binary_search_index(address, &mut probes)?
+        } else {
+            self.linear_search_index(address, &mut probes)?
+        };
+        #[cfg(test)]
+        self.index_probes.fetch_add(probes, Ordering::Relaxed);
+        let index_offset =
+            index_offset.filter(|index_offset| !self.is_skipped_entry(*index_offset));
+
+        if let (Some(index_offset), Some(cache)) = (index_offset, &self.lookup_cache) {
+            cache.insert(address, index_offset);
+        }
+        Ok(index_offset)
+    }
+
+    /// Scans the index block for the specified address, adding the number
+    /// of compared addresses to `probes`.
+    fn linear_search_index(
+        &self,
+        address: &Pubkey,
+        probes: &mut u64,
+    ) -> TieredStorageResult<Option<IndexOffset>> {
+        for i in 0..self.footer.account_entry_count {
+            *probes += 1;
+            if self.get_account_address(IndexOffset(i))? == address {
+                return Ok(Some(IndexOffset(i)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Binary-searches the index block, which must be sorted by address,
+    /// for the specified address, adding the number of compared addresses
+    /// to `probes`.
+    fn binary_search_index(
+        &self,
+        address: &Pubkey,
+        probes: &mut u64,
+    ) -> TieredStorageResult<Option<IndexOffset>> {
+        let (mut low, mut high) = (0, self.footer.account_entry_count);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            *probes += 1;
+            match self.get_account_address(IndexOffset(mid))?.cmp(address) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Ok(Some(IndexOffset(mid))),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the hash of the account of the specified address, or None if
+    /// it is not stored in this file.  Only the account meta and the
+    /// optional fields are read, not the account data.
+    ///
+    /// Zero-lamport accounts, which are stored without a hash, return the
+    /// default hash, same as StoredAccountMeta::hash().
+    pub fn get_hash(&self, address: &Pubkey) -> TieredStorageResult<Option<AccountHash>> {
+        let Some(index_offset) = self.find_index_offset(address)? else {
+            return Ok(None);
+        };
+        let (account_offset, meta) = self.get_account_meta(index_offset)?;
+        let account_block = self.get_account_block(account_offset, index_offset)?;
+        Ok(Some(
+            meta.account_hash(account_block)
+                .copied()
+                .unwrap_or(AccountHash(Hash::default())),
+        ))
+    }
+
+    /// Returns Ok(()) if the index block holds `num_accounts()` entries
+    /// whose account offsets are strictly increasing and point within the
+    /// account blocks, and TieredStorageError::CorruptIndex otherwise.
+    ///
+    /// When the index is sorted by address, the addresses must be strictly
+    /// increasing instead, and the account offsets must all be distinct.
+    pub fn validate_index(&self) -> TieredStorageResult<()> {
+        let entry_size = self
+            .footer
+            .index_block_format
+            .entry_size::<HotAccountOffset>();
+        let index_block_size = self
+            .footer
+            .owners_block_offset
+            .saturating_sub(self.footer.index_block_offset);
+        let num_entries = index_block_size / entry_size as u64;
+        if num_entries < self.footer.account_entry_count as u64 {
+            return Err(TieredStorageError::CorruptIndex(
+                num_entries as u32,
+                format!(
+                    "the index block of {index_block_size} bytes cannot hold {} entries",
+                    self.footer.account_entry_count
+                ),
+            ));
+        }
+
+        let index_sorted = self.footer.index_sorted();
+        let mut offsets = Vec::with_capacity(self.footer.account_entry_count as usize);
+        let mut prev_address = None;
+        for i in 0..self.footer.account_entry_count {
+            let account_offset = self.get_account_offset(IndexOffset(i))?;
+            self.check_account_offset(IndexOffset(i), account_offset)?;
+            let offset = account_offset.offset();
+            if index_sorted {
+                let address = self.get_account_address(IndexOffset(i))?;
+                if let Some(prev_address) =
+                    prev_address.filter(|prev_address| address <= *prev_address)
+                {
+                    return Err(TieredStorageError::CorruptIndex(
+                        i,
+                        format!(
+                            "address {address} does not follow the previous address \
+                             {prev_address}"
+                        ),
+                    ));
+                }
+                prev_address = Some(address);
+            }
+            offsets.push((offset, i));
+        }
+
+        // the account blocks are in index order, unless the index is sorted
+        // by address, and they never overlap
+        if index_sorted {
+            offsets.sort_unstable();
+        }
+        for window in offsets.windows(2) {
+            let ((prev_offset, _), (offset, i)) = (window[0], window[1]);
+            if offset <= prev_offset {
+                return Err(TieredStorageError::CorruptIndex(
+                    i,
+                    format!(
+                        "account offset {offset} does not follow the previous offset \
+                         {prev_offset}"
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the address of the account owner given the specified
     /// owner_offset.
     fn get_owner_address(&self, owner_offset: OwnerOffset) -> TieredStorageResult<&Pubkey> {
@@ -399,13 +834,21 @@ impl HotStorageReader {
         // Obtain the ending offset of the account block.  If the current
         // account is the last account, then the ending offset is the
         // index_block_offset.
-        let account_block_ending_offset =
-            if index_offset.0.saturating_add(1) == self.footer.account_entry_count {
+        let account_block_ending_offset = if let Some(offsets) = &self.sorted_account_offsets {
+            // the index is sorted by address, so the next account block
+            // starts at the next larger offset
+            offsets
+                .get(offsets.partition_point(|offset| *offset <= account_meta_offset))
+                .copied()
+                .unwrap_or(self.footer.index_block_offset as usize)
+        } else {
+            let next = self.first_valid_entry(IndexOffset(index_offset.0.saturating_add(1)));
+            if next.0 == self.footer.account_entry_count {
                 self.footer.index_block_offset as usize
             } else {
-                self.get_account_offset(IndexOffset(index_offset.0.saturating_add(1)))?
-                    .offset()
-            };
+                self.get_account_offset(next)?.offset()
+            }
+        };
 
         // With the ending offset, minus the starting offset (i.e.,
         // the account meta offset) and the HotAccountMeta size, the reminder
@@ -431,32 +874,297 @@ impl HotStorageReader {
         Ok(data)
     }
 
+    /// Returns Ok(true) if the specified index offset refers to an account,
+    /// Ok(false) if it equals the number of accounts, which allows iteration
+    /// to terminate, and TieredStorageError::IndexOffsetOutOfBounds when it
+    /// goes beyond.
+    fn contains_index_offset(&self, index_offset: IndexOffset) -> TieredStorageResult<bool> {
+        match index_offset.0.cmp(&self.footer.account_entry_count) {
+            std::cmp::Ordering::Less => Ok(true),
+            std::cmp::Ordering::Equal => Ok(false),
+            std::cmp::Ordering::Greater => Err(TieredStorageError::IndexOffsetOutOfBounds(
+                index_offset.0,
+                self.footer.account_entry_count,
+            )),
+        }
+    }
+
+    /// Returns the StoredAccountInfo of the account located at the specified
+    /// index offset, the same as the one returned by write_accounts() when
+    /// the account was written.
+    ///
+    /// Returns Ok(None) when the index offset equals the number of accounts,
+    /// and TieredStorageError::IndexOffsetOutOfBounds when it goes beyond.
+    pub fn get_stored_account_info(
+        &self,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<Option<StoredAccountInfo>> {
+        if !self.contains_index_offset(index_offset)? {
+            return Ok(None);
+        }
+
+        let account_offset = self.get_account_offset(index_offset)?;
+        let stored_size = std::mem::size_of::<HotAccountMeta>()
+            + self.get_account_block_size(account_offset, index_offset)?;
+
+        Ok(Some(StoredAccountInfo {
+            offset: index_offset.0 as usize,
+            size: stored_size
+                + self
+                    .footer
+                    .index_block_format
+                    .entry_size::<HotAccountOffset>(),
+        }))
+    }
+
     /// Returns the account located at the specified index offset.
+    ///
+    /// Returns Ok(None) when the index offset equals the number of accounts,
+    /// and TieredStorageError::IndexOffsetOutOfBounds when it goes beyond.
+    /// Entries skipped by with_corrupt_entries_skipped() are passed over,
+    /// both at the specified index offset and in the returned next index
+    /// offset.
     pub fn get_account(
         &self,
         index_offset: IndexOffset,
     ) -> TieredStorageResult<Option<(StoredAccountMeta<'_>, IndexOffset)>> {
-        if index_offset.0 >= self.footer.account_entry_count {
+        let index_offset = self.first_unskipped_entry(index_offset);
+        if !self.contains_index_offset(index_offset)? {
             return Ok(None);
         }
 
-        let account_offset = self.get_account_offset(index_offset)?;
+        Ok(Some((
+            self.read_account(index_offset)?,
+            self.first_unskipped_entry(IndexOffset(index_offset.0.saturating_add(1))),
+        )))
+    }
 
-        let meta = self.get_account_meta_from_offset(account_offset)?;
+    /// Returns the account of the index entry at the specified index
+    /// offset, which must refer to an account.  Unlike get_account(), a
+    /// skipped entry is not passed over.
+    fn read_account(
+        &self,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<StoredAccountMeta<'_>> {
+        let (account_offset, meta) = self.get_account_meta(index_offset)?;
         let address = self.get_account_address(index_offset)?;
         let owner = self.get_owner_address(meta.owner_offset())?;
         let account_block = self.get_account_block(account_offset, index_offset)?;
 
-        Ok(Some((
-            StoredAccountMeta::Hot(TieredReadableAccount {
-                meta,
-                address,
-                owner,
-                index: index_offset,
-                account_block,
-            }),
-            IndexOffset(index_offset.0.saturating_add(1)),
-        )))
+        Ok(StoredAccountMeta::Hot(TieredReadableAccount {
+            meta,
+            address,
+            owner,
+            index: index_offset,
+            account_block,
+        }))
+    }
+
+    /// Same as get_account(), but the pages of the account block are first
+    /// faulted in on tokio's blocking thread pool, so that the read itself
+    /// does not block the async executor on disk I/O.  The index and
+    /// owners blocks are still read from the calling thread.
+    ///
+    /// Must be called from within a tokio runtime.
+    #[cfg(feature = "async")]
+    pub async fn get_account_async(
+        &self,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<Option<(StoredAccountMeta<'_>, IndexOffset)>> {
+        let index_offset = self.first_unskipped_entry(index_offset);
+        if !self.contains_index_offset(index_offset)? {
+            return Ok(None);
+        }
+
+        let account_offset = self.get_account_offset(index_offset)?;
+        let account_size = std::mem::size_of::<HotAccountMeta>()
+            .saturating_add(self.get_account_block_size(account_offset, index_offset)?);
+        let mmap = Arc::clone(&self.mmap);
+        tokio::task::spawn_blocking(move || {
+            crate::tiered_storage::mmap_utils::touch_pages(
+                &mmap,
+                account_offset.offset(),
+                account_size,
+            )
+        })
+        .await
+        .map_err(std::io::Error::from)?;
+
+        self.get_account(index_offset)
+    }
+
+    /// Same as get_account(), but in addition advises the kernel to read
+    /// ahead the pages of the next account, so that they are likely to be
+    /// resident by the time the caller moves on to it.
+    ///
+    /// Nothing is prefetched for the last account.
+    pub fn get_account_prefetched(
+        &self,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<Option<(StoredAccountMeta<'_>, IndexOffset)>> {
+        let account = self.get_account(index_offset)?;
+        if let Some((_, next)) = &account {
+            if self.contains_index_offset(*next)? {
+                let next_account_offset = self.get_account_offset(*next)?;
+                let next_account_size = std::mem::size_of::<HotAccountMeta>()
+                    + self.get_account_block_size(next_account_offset, *next)?;
+                prefetch(&self.mmap, next_account_offset.offset(), next_account_size);
+            }
+        }
+        Ok(account)
+    }
+
+    /// Returns the accounts of the specified addresses, in the same order as
+    /// the input addresses, with None for the addresses not in this file.
+    ///
+    /// The index block is scanned once to resolve all the addresses, and the
+    /// accounts are then read in IndexOffset order, which matches the order
+    /// of their account blocks, to improve locality.
+    pub fn get_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> TieredStorageResult<Vec<Option<StoredAccountMeta<'_>>>> {
+        let requested: HashSet<_> = addresses.iter().collect();
+        let mut index_offsets = HashMap::with_capacity(requested.len());
+        for index_offset in self.unskipped_entries() {
+            let address = self.get_account_address(index_offset)?;
+            if requested.contains(address) {
+                index_offsets.insert(address, index_offset);
+            }
+        }
+
+        let mut sorted_positions: Vec<_> = addresses
+            .iter()
+            .enumerate()
+            .filter_map(|(position, address)| {
+                index_offsets
+                    .get(address)
+                    .map(|index_offset| (*index_offset, position))
+            })
+            .collect();
+        sorted_positions.sort_unstable_by_key(|(index_offset, _)| index_offset.0);
+
+        let mut accounts: Vec<_> = std::iter::repeat_with(|| None)
+            .take(addresses.len())
+            .collect();
+        for (index_offset, position) in sorted_positions {
+            accounts[position] = Some(self.read_account(index_offset)?);
+        }
+        Ok(accounts)
+    }
+
+    /// Returns the accounts owned by the specified owner, in IndexOffset
+    /// order.
+    ///
+    /// The owner is first resolved to its OwnerOffset through the owners
+    /// block, so each account only needs its meta to be read to decide
+    /// whether it matches.  Zero-lamport accounts, which are stored with
+    /// the OWNER_NO_OWNER placeholder, never match.
+    pub fn accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+    ) -> TieredStorageResult<Vec<StoredAccountMeta<'_>>> {
+        let mut owner_offset = None;
+        for i in 0..self.footer.owner_count {
+            if self.get_owner_address(OwnerOffset(i))? == owner {
+                owner_offset = Some(OwnerOffset(i));
+                break;
+            }
+        }
+        let Some(owner_offset) = owner_offset else {
+            return Ok(vec![]);
+        };
+        let is_no_owner = owner == &*OWNER_NO_OWNER;
+
+        let mut accounts = vec![];
+        for index_offset in self.unskipped_entries() {
+            let (_, meta) = self.get_account_meta(index_offset)?;
+            if meta.owner_offset() != owner_offset || (is_no_owner && meta.lamports() == 0) {
+                continue;
+            }
+            accounts.push(self.read_account(index_offset)?);
+        }
+        Ok(accounts)
+    }
+
+    /// Returns an iterator over the accounts whose owner is not one of the
+    /// specified owners, in IndexOffset order.
+    ///
+    /// The specified owners are first resolved against the owners block so
+    /// that each account only needs its meta to be read to decide whether it
+    /// should be skipped.
+    pub fn accounts_not_owned_by(
+        &self,
+        owners: &HashSet<Pubkey>,
+    ) -> impl Iterator<Item = TieredStorageResult<StoredAccountMeta<'_>>> + '_ {
+        let (excluded_owner_offsets, error) = match self.owners_table() {
+            Ok(owners_table) => (
+                owners_table
+                    .iter()
+                    .map(|owner| owners.contains(owner))
+                    .collect::<Vec<_>>(),
+                None,
+            ),
+            Err(err) => (vec![], Some(Err(err))),
+        };
+        let index_offsets = error
+            .is_none()
+            .then(|| self.unskipped_entries())
+            .into_iter()
+            .flatten();
+
+        error
+            .into_iter()
+            .chain(index_offsets.filter_map(move |index_offset| {
+                let owner_offset = match self.get_account_meta(index_offset) {
+                    Ok((_, meta)) => meta.owner_offset(),
+                    Err(err) => return Some(Err(err)),
+                };
+                if excluded_owner_offsets
+                    .get(owner_offset.0 as usize)
+                    .copied()
+                    .unwrap_or(false)
+                {
+                    return None;
+                }
+                Some(self.read_account(index_offset))
+            }))
+    }
+
+    /// Returns an EfficiencyReport of the underlying hot accounts file,
+    /// computed from the footer and a single scan over the account metas.
+    pub fn efficiency_report(&self) -> TieredStorageResult<EfficiencyReport> {
+        let mut data_bytes = 0;
+        let mut slack_bytes = 0;
+        for index_offset in self.unskipped_entries() {
+            let (account_offset, meta) = self.get_account_meta(index_offset)?;
+            let account_block = self.get_account_block(account_offset, index_offset)?;
+            data_bytes += meta.account_data_size(account_block);
+            slack_bytes += meta.account_data_padding() as usize;
+        }
+
+        // the index block is padded to HOT_BLOCK_ALIGNMENT when it has an
+        // odd number of entries.
+        let index_entries_size = self.num_accounts()
+            * (std::mem::size_of::<Pubkey>() + std::mem::size_of::<HotAccountOffset>());
+        let index_block_size = (self.footer.owners_block_offset as usize)
+            .saturating_sub(self.footer.index_block_offset as usize);
+        slack_bytes += index_block_size.saturating_sub(index_entries_size);
+
+        let file_size = self.mmap.len();
+        let overhead_bytes = file_size.saturating_sub(data_bytes + slack_bytes);
+
+        Ok(EfficiencyReport {
+            file_size,
+            data_bytes,
+            slack_bytes,
+            overhead_bytes,
+            overhead_ratio: overhead_bytes as f64 / file_size as f64,
+            // hot account blocks are always stored uncompressed
+            compression_ratio: 1.0,
+            dedup_savings_bytes: self.num_accounts().saturating_sub(self.num_owners())
+                * std::mem::size_of::<Pubkey>(),
+        })
     }
 
     /// Return a vector of account metadata for each account, starting from
@@ -476,6 +1184,57 @@ impl HotStorageReader {
         }
         Ok(accounts)
     }
+
+    /// Returns an iterator over the accounts in IndexOffset order, each
+    /// together with its IndexOffset and the byte offset of its account
+    /// block within the account blocks, as taken by raw_account_block().
+    ///
+    /// The iteration stops after the first error.
+    pub fn accounts_with_offsets(
+        &self,
+    ) -> impl Iterator<Item = TieredStorageResult<(StoredAccountMeta<'_>, IndexOffset, u64)>> + '_
+    {
+        let mut next = Some(IndexOffset(0));
+        std::iter::from_fn(move || {
+            let index_offset = next.take()?;
+            let account = self.get_account(index_offset).transpose()?;
+            Some(account.and_then(|(account, next_index_offset)| {
+                // skipped entries may have been passed over
+                let index_offset = IndexOffset(account.offset() as u32);
+                let account_offset = self.get_account_offset(index_offset)?;
+                next = Some(next_index_offset);
+                Ok((
+                    account,
+                    index_offset,
+                    (account_offset.offset() as u64)
+                        .saturating_sub(self.footer.account_blocks_offset()),
+                ))
+            }))
+        })
+    }
+
+    /// Returns up to `count` accounts starting from `start`, stopping early
+    /// when the end of the file is reached.  An empty vector is returned if
+    /// `start` is at or past the end.
+    pub fn get_account_range(
+        &self,
+        start: IndexOffset,
+        count: usize,
+    ) -> TieredStorageResult<Vec<StoredAccountMeta>> {
+        let end = (start.0 as usize)
+            .saturating_add(count)
+            .min(self.footer.account_entry_count as usize);
+        let mut accounts = Vec::with_capacity(end.saturating_sub(start.0 as usize));
+        let mut index_offset = start;
+        while (index_offset.0 as usize) < end {
+            let Some((account, next)) = self.get_account(index_offset)? else {
+                break;
+            };
+            accounts.push(account);
+            index_offset = next;
+        }
+        Ok(accounts)
+    }
 }
 
 fn write_optional_fields(
@@ -495,59 +1254,220 @@ fn write_optional_fields(
     Ok(size)
 }
 
+/// The fields of an account, other than its data, that are persisted by
+/// HotStorageWriter::write_account_streaming().
+#[derive(Debug)]
+pub struct StreamingAccountMeta<'a> {
+    /// The address of the account
+    pub address: &'a Pubkey,
+    /// The lamports balance of the account
+    pub lamports: u64,
+    /// The address of the account owner
+    pub owner: &'a Pubkey,
+    /// Whether the account is executable
+    pub executable: bool,
+    /// The epoch at which the account will next owe rent
+    pub rent_epoch: Epoch,
+    /// The hash of the account
+    pub account_hash: &'a AccountHash,
+}
+
 /// The writer that creates a hot accounts file.
 #[derive(Debug)]
 pub struct HotStorageWriter {
     storage: TieredStorageFile,
+    /// The expected number of accounts to be written, used to pre-size the
+    /// in-memory index and owners tables.
+    expected_accounts: usize,
+    /// Whether the index block is sorted by address.
+    sort_index: bool,
+    /// Whether duplicate addresses are collapsed instead of rejected.
+    collapse_duplicates: bool,
+    /// The number of accounts dropped by collapsing duplicate addresses.
+    collapsed_duplicates: AtomicUsize,
 }
 
 impl HotStorageWriter {
     /// Create a new HotStorageWriter with the specified path.
     pub fn new(file_path: impl AsRef<Path>) -> TieredStorageResult<Self> {
+        Self::with_capacity(file_path, 0)
+    }
+
+    /// Create a new HotStorageWriter with the specified path that expects
+    /// `expected_accounts` accounts to be written.  This avoids growing the
+    /// in-memory index and owners tables while writing a large batch.
+    pub fn with_capacity(
+        file_path: impl AsRef<Path>,
+        expected_accounts: usize,
+    ) -> TieredStorageResult<Self> {
         Ok(Self {
             storage: TieredStorageFile::new_writable(file_path)?,
+            expected_accounts,
+            sort_index: false,
+            collapse_duplicates: false,
+            collapsed_duplicates: AtomicUsize::default(),
         })
     }
 
+    /// Sorts the index block by address, so that readers can look up an
+    /// address with a binary search.  The account blocks are still written
+    /// in the order of the input accounts.
+    pub fn with_sorted_index(mut self) -> Self {
+        self.sort_index = true;
+        self
+    }
+
+    /// Keeps only the last of the accounts sharing the same address instead
+    /// of failing with TieredStorageError::DuplicatePubkey.  The
+    /// StoredAccountInfo of a dropped account points to the kept account
+    /// with a size of 0, and collapsed_duplicates() reports how many
+    /// accounts have been dropped.
+    ///
+    /// Note that the accounts are then collected before being written.
+    pub fn with_duplicates_collapsed(mut self) -> Self {
+        self.collapse_duplicates = true;
+        self
+    }
+
+    /// Returns the number of accounts dropped by the last write because a
+    /// later account has the same address.  Always 0 unless the writer was
+    /// created with_duplicates_collapsed().
+    pub fn collapsed_duplicates(&self) -> usize {
+        self.collapsed_duplicates.load(Ordering::Relaxed)
+    }
+
+    /// Flushes everything written so far, including the file metadata,
+    /// to disk so that the file survives a crash.
+    pub fn flush_and_sync(&self) -> TieredStorageResult<()> {
+        Ok(self.storage.sync_all()?)
+    }
+
     /// Persists an account with the specified information and returns
     /// the stored size of the account.
+    ///
+    /// The `data_len` bytes of account data are written by `write_data`,
+    /// which returns the number of bytes it has written.
     fn write_account(
         &self,
         lamports: u64,
         owner_offset: OwnerOffset,
-        account_data: &[u8],
         executable: bool,
-        rent_epoch: Option<Epoch>,
-        account_hash: Option<&AccountHash>,
+        optional_fields: &AccountMetaOptionalFields,
+        data_len: usize,
+        write_data: impl FnOnce(&TieredStorageFile) -> TieredStorageResult<usize>,
     ) -> TieredStorageResult<usize> {
-        let optional_fields = AccountMetaOptionalFields {
-            rent_epoch,
-            account_hash,
-        };
-
-        let mut flags = AccountMetaFlags::new_from(&optional_fields);
+        let mut flags = AccountMetaFlags::new_from(optional_fields);
         flags.set_executable(executable);
 
-        let padding_len = padding_bytes(account_data.len());
+        let padding_len = padding_bytes(data_len);
         let meta = HotAccountMeta::new()
             .with_lamports(lamports)
             .with_owner_offset(owner_offset)
-            .with_account_data_size(account_data.len() as u64)
+            .with_account_data_size(data_len as u64)
             .with_account_data_padding(padding_len)
             .with_flags(&flags);
 
         let mut stored_size = 0;
 
         stored_size += self.storage.write_pod(&meta)?;
-        stored_size += self.storage.write_bytes(account_data)?;
+        stored_size += write_data(&self.storage)?;
         stored_size += self
             .storage
             .write_bytes(&PADDING_BUFFER[0..(padding_len as usize)])?;
-        stored_size += write_optional_fields(&self.storage, &optional_fields)?;
+        stored_size += write_optional_fields(&self.storage, optional_fields)?;
 
         Ok(stored_size)
     }
 
+    /// Persists the index block, the owners block, and the footer, in
+    /// that order, right after the account blocks that end at `cursor`.
+    /// Returns the persisted footer.
+    fn write_index_owners_and_footer(
+        &self,
+        mut footer: TieredStorageFooter,
+        mut cursor: usize,
+        index: &[AccountIndexWriterEntry<HotAccountOffset>],
+        owners_table: &OwnersTable,
+    ) -> TieredStorageResult<TieredStorageFooter> {
+        footer.account_entry_count = index.len() as u32;
+
+        // writing index block
+        // expect the offset of each block aligned.
+        assert!(cursor % HOT_BLOCK_ALIGNMENT == 0);
+        footer.index_block_offset = cursor as u64;
+        cursor += footer
+            .index_block_format
+            .write_index_block(&self.storage, index)?;
+        if cursor % HOT_BLOCK_ALIGNMENT != 0 {
+            // In case it is not yet aligned, it is due to the fact that
+            // the index block has an odd number of entries.  In such case,
+            // we expect the amount off is equal to 4.
+            assert_eq!(cursor % HOT_BLOCK_ALIGNMENT, 4);
+            cursor += self.storage.write_pod(&0u32)?;
+        }
+
+        // writing owners block
+        assert!(cursor % HOT_BLOCK_ALIGNMENT == 0);
+        footer.owners_block_offset = cursor as u64;
+        footer.owner_count = owners_table.len() as u32;
+        footer
+            .owners_block_format
+            .write_owners_block(&self.storage, owners_table)?;
+
+        footer.write_footer_block(&self.storage)?;
+
+        Ok(footer)
+    }
+
+    /// Persists a single account into the underlying hot accounts file
+    /// associated with this HotStorageWriter, streaming its `len` bytes of
+    /// account data from `data_reader` in chunks instead of requiring the
+    /// data as a contiguous slice.  This keeps the memory footprint low
+    /// when writing very large accounts.
+    ///
+    /// The resulting file holds exactly this one account for `slot`, and has
+    /// the same layout as a file produced by write_accounts() for the same
+    /// account and slot.
+    pub fn write_account_streaming(
+        &self,
+        meta: &StreamingAccountMeta,
+        data_reader: impl Read,
+        len: usize,
+        slot: Slot,
+    ) -> TieredStorageResult<StoredAccountInfo> {
+        let footer = TieredStorageFooter {
+            slot,
+            ..new_hot_footer()
+        };
+        let mut owners_table = OwnersTable::with_capacity(1);
+        let owner_offset = owners_table.insert(meta.owner);
+        let optional_fields = AccountMetaOptionalFields {
+            // only persist rent_epoch for those rent-paying accounts
+            rent_epoch: (meta.rent_epoch != RENT_EXEMPT_RENT_EPOCH).then_some(meta.rent_epoch),
+            account_hash: Some(meta.account_hash),
+        };
+        let stored_size = self.write_account(
+            meta.lamports,
+            owner_offset,
+            meta.executable,
+            &optional_fields,
+            len,
+            |storage| Ok(storage.write_from_reader(data_reader, len)?),
+        )?;
+
+        let index = [AccountIndexWriterEntry {
+            address: meta.address,
+            offset: HotAccountOffset::new(0)?,
+        }];
+        let stored_info = StoredAccountInfo {
+            offset: 0,
+            size: stored_size + footer.index_block_format.entry_size::<HotAccountOffset>(),
+        };
+        self.write_index_owners_and_footer(footer, stored_size, &index, &owners_table)?;
+
+        Ok(stored_info)
+    }
+
     /// Persists `accounts` into the underlying hot accounts file associated
     /// with this HotStorageWriter.  The first `skip` number of accounts are
     /// *not* persisted.
@@ -562,17 +1482,139 @@ impl HotStorageWriter {
         accounts: &StorableAccountsWithHashesAndWriteVersions<'a, 'b, T, U, V>,
         skip: usize,
     ) -> TieredStorageResult<Vec<StoredAccountInfo>> {
-        let mut footer = new_hot_footer();
-        let mut index = vec![];
-        let mut owners_table = OwnersTable::default();
+        self.write_accounts_with_footer(accounts, skip)
+            .map(|(stored_infos, _footer)| stored_infos)
+    }
+
+    /// Same as write_accounts(), but also returns the footer that has been
+    /// persisted.
+    pub fn write_accounts_with_footer<
+        'a,
+        'b,
+        T: ReadableAccount + Sync,
+        U: StorableAccounts<'a, T>,
+        V: Borrow<AccountHash>,
+    >(
+        &self,
+        accounts: &StorableAccountsWithHashesAndWriteVersions<'a, 'b, T, U, V>,
+        skip: usize,
+    ) -> TieredStorageResult<(Vec<StoredAccountInfo>, TieredStorageFooter)> {
+        let footer = TieredStorageFooter {
+            slot: accounts.accounts.target_slot(),
+            ..new_hot_footer()
+        };
+        let entries = (skip..accounts.accounts.len()).map(|i| {
+            // write versions are not persisted in hot accounts files
+            let (account, address, account_hash, _write_version) = accounts.get(i);
+            (account, address, account_hash)
+        });
+        self.write_account_entries(footer, entries)
+    }
+
+    /// Same as write_accounts(), but takes the accounts as an iterator of
+    /// `(address, account, account_hash, write_version)`, so that they do
+    /// not need to be materialized up front.  The accounts are written as
+    /// they are yielded, and the resulting file is identical to the one
+    /// written by write_accounts() for the same accounts and slot.
+    pub fn write_accounts_iter<'a, T: ReadableAccount + 'a>(
+        &self,
+        accounts: impl IntoIterator<Item = (&'a Pubkey, &'a T, &'a AccountHash, StoredMetaWriteVersion)>,
+        slot: Slot,
+    ) -> TieredStorageResult<Vec<StoredAccountInfo>> {
+        let footer = TieredStorageFooter {
+            slot,
+            ..new_hot_footer()
+        };
+        let entries =
+            accounts
+                .into_iter()
+                .map(|(address, account, account_hash, _write_version)| {
+                    // zero-lamport accounts are stored without their fields,
+                    // same as StorableAccountsWithHashesAndWriteVersions::get()
+                    let account = (account.lamports() != 0).then_some(account);
+                    (account, address, account_hash)
+                });
+        self.write_account_entries(footer, entries)
+            .map(|(stored_infos, _footer)| stored_infos)
+    }
+
+    /// Writes the specified `(account, address, account_hash)` entries,
+    /// where a None account denotes a zero-lamport account, followed by
+    /// the index block, the owners block, and `footer`.
+    ///
+    /// Duplicate addresses are rejected, or collapsed to their last entry
+    /// if the writer was created with_duplicates_collapsed().
+    fn write_account_entries<'a, T: ReadableAccount + 'a>(
+        &self,
+        footer: TieredStorageFooter,
+        entries: impl Iterator<Item = (Option<&'a T>, &'a Pubkey, &'a AccountHash)>,
+    ) -> TieredStorageResult<(Vec<StoredAccountInfo>, TieredStorageFooter)> {
+        if !self.collapse_duplicates {
+            return self.write_unique_account_entries(footer, entries);
+        }
+
+        let entries: Vec<_> = entries.collect();
+        let last_positions: HashMap<&Pubkey, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, (_account, address, _account_hash))| (*address, i))
+            .collect();
+        let is_kept = |i: usize, address: &Pubkey| last_positions[address] == i;
+        let (kept_stored_infos, footer) = self.write_unique_account_entries(
+            footer,
+            entries
+                .iter()
+                .enumerate()
+                .filter(|(i, (_account, address, _account_hash))| is_kept(*i, address))
+                .map(|(_i, entry)| *entry),
+        )?;
+
+        // Map each position to the StoredAccountInfo of the kept account
+        // sharing its address.
+        let mut kept_stored_info_indices = vec![0; entries.len()];
+        let mut num_kept = 0;
+        for (i, (_account, address, _account_hash)) in entries.iter().enumerate() {
+            if is_kept(i, address) {
+                kept_stored_info_indices[i] = num_kept;
+                num_kept += 1;
+            }
+        }
+        let stored_infos = entries
+            .iter()
+            .enumerate()
+            .map(|(i, (_account, address, _account_hash))| {
+                let kept = &kept_stored_infos[kept_stored_info_indices[last_positions[address]]];
+                StoredAccountInfo {
+                    offset: kept.offset,
+                    size: if is_kept(i, address) { kept.size } else { 0 },
+                }
+            })
+            .collect();
+        self.collapsed_duplicates
+            .store(entries.len() - num_kept, Ordering::Relaxed);
+
+        Ok((stored_infos, footer))
+    }
+
+    /// Same as write_account_entries(), but fails with
+    /// TieredStorageError::DuplicatePubkey at the first repeated address.
+    fn write_unique_account_entries<'a, T: ReadableAccount + 'a>(
+        &self,
+        mut footer: TieredStorageFooter,
+        entries: impl Iterator<Item = (Option<&'a T>, &'a Pubkey, &'a AccountHash)>,
+    ) -> TieredStorageResult<(Vec<StoredAccountInfo>, TieredStorageFooter)> {
+        let mut addresses = HashSet::with_capacity(self.expected_accounts);
+        let mut index = Vec::with_capacity(self.expected_accounts);
+        // the number of accounts is an upper bound of the number of owners
+        let mut owners_table = OwnersTable::with_capacity(self.expected_accounts);
         let mut cursor = 0;
 
         // writing accounts blocks
-        let len = accounts.accounts.len();
-        let total_input_accounts = len - skip;
-        let mut stored_infos = Vec::with_capacity(total_input_accounts);
-        for i in skip..len {
-            let (account, address, account_hash, _write_version) = accounts.get(i);
+        let mut stored_infos = Vec::with_capacity(self.expected_accounts);
+        for (account, address, account_hash) in entries {
+            if !addresses.insert(address) {
+                return Err(TieredStorageError::DuplicatePubkey(*address));
+            }
             let index_entry = AccountIndexWriterEntry {
                 address,
                 offset: HotAccountOffset::new(cursor)?,
@@ -594,13 +1636,17 @@ impl HotStorageWriter {
                 })
                 .unwrap_or((0, &OWNER_NO_OWNER, &[], false, None, None));
             let owner_offset = owners_table.insert(owner);
+            let optional_fields = AccountMetaOptionalFields {
+                rent_epoch,
+                account_hash,
+            };
             let stored_size = self.write_account(
                 lamports,
                 owner_offset,
-                data,
                 executable,
-                rent_epoch,
-                account_hash,
+                &optional_fields,
+                data.len(),
+                |storage| Ok(storage.write_bytes(data)?),
             )?;
             cursor += stored_size;
 
@@ -620,34 +1666,25 @@ impl HotStorageWriter {
             });
             index.push(index_entry);
         }
-        footer.account_entry_count = total_input_accounts as u32;
 
-        // writing index block
-        // expect the offset of each block aligned.
-        assert!(cursor % HOT_BLOCK_ALIGNMENT == 0);
-        footer.index_block_offset = cursor as u64;
-        cursor += footer
-            .index_block_format
-            .write_index_block(&self.storage, &index)?;
-        if cursor % HOT_BLOCK_ALIGNMENT != 0 {
-            // In case it is not yet aligned, it is due to the fact that
-            // the index block has an odd number of entries.  In such case,
-            // we expect the amount off is equal to 4.
-            assert_eq!(cursor % HOT_BLOCK_ALIGNMENT, 4);
-            cursor += self.storage.write_pod(&0u32)?;
+        if self.sort_index {
+            footer.index_block_format = IndexBlockFormat::SortedAddressesThenOffsets;
+            // Keep the StoredAccountInfos in input order, but point each of
+            // them to where its account ends up in the sorted index.
+            let mut order: Vec<_> = (0..index.len()).collect();
+            order.sort_by_key(|i| index[*i].address);
+            for (index_offset, i) in order.iter().enumerate() {
+                stored_infos[*i].offset = index_offset;
+            }
+            let mut entries: Vec<_> = index.into_iter().map(Some).collect();
+            index = order
+                .into_iter()
+                .map(|i| entries[i].take().unwrap())
+                .collect();
         }
+        let footer = self.write_index_owners_and_footer(footer, cursor, &index, &owners_table)?;
 
-        // writing owners block
-        assert!(cursor % HOT_BLOCK_ALIGNMENT == 0);
-        footer.owners_block_offset = cursor as u64;
-        footer.owner_count = owners_table.len() as u32;
-        footer
-            .owners_block_format
-            .write_owners_block(&self.storage, &owners_table)?;
-
-        footer.write_footer_block(&self.storage)?;
-
-        Ok(stored_infos)
+        Ok((stored_infos, footer))
     }
 }
 
@@ -660,22 +1697,31 @@ pub mod tests {
             tiered_storage::{
                 byte_block::ByteBlockWriter,
                 file::TieredStorageFile,
-                footer::{AccountBlockFormat, AccountMetaFormat, TieredStorageFooter, FOOTER_SIZE},
+                footer::{
+                    AccountBlockFormat, AccountMetaFormat, TieredStorageFooter,
+                    FOOTER_FORMAT_VERSION, FOOTER_SIZE,
+                },
                 hot::{HotAccountMeta, HotStorageReader},
                 index::{AccountIndexWriterEntry, IndexBlockFormat, IndexOffset},
                 meta::{AccountMetaFlags, AccountMetaOptionalFields, TieredAccountMeta},
                 owners::{OwnersBlockFormat, OwnersTable},
+                test_utils::{
+                    new_account_hashes, with_storable_accounts, write_hot_accounts,
+                    write_hot_accounts_with_hashes,
+                },
             },
         },
         assert_matches::assert_matches,
         memoffset::offset_of,
         rand::{seq::SliceRandom, Rng},
         solana_sdk::{
-            account::{Account, AccountSharedData, ReadableAccount},
+            account::{Account, AccountSharedData, ReadableAccount, WritableAccount},
             hash::Hash,
             pubkey::Pubkey,
             slot_history::Slot,
             stake_history::Epoch,
+            system_instruction::MAX_PERMITTED_DATA_LENGTH,
+            system_program,
         },
         tempfile::TempDir,
     };
@@ -852,8 +1898,9 @@ pub mod tests {
             hash: Hash::new_unique(),
             min_account_address: Pubkey::default(),
             max_account_address: Pubkey::new_unique(),
+            slot: 1234,
             footer_size: FOOTER_SIZE as u64,
-            format_version: 1,
+            format_version: FOOTER_FORMAT_VERSION,
         };
 
         {
@@ -921,7 +1968,6 @@ pub mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "would exceed accounts blocks offset boundary")]
     fn test_get_acount_meta_from_offset_out_of_bounds() {
         // Generate a new temp path that is guaranteed to NOT already have a file.
         let temp_dir = TempDir::new().unwrap();
@@ -943,8 +1989,11 @@ pub mod tests {
         let hot_storage = HotStorageReader::new_from_path(&path).unwrap();
         let offset = HotAccountOffset::new(footer.index_block_offset as usize).unwrap();
         // Read from index_block_offset, which offset doesn't belong to
-        // account blocks.  Expect assert failure here
-        hot_storage.get_account_meta_from_offset(offset).unwrap();
+        // account blocks.  Expect an error here
+        assert_matches!(
+            hot_storage.get_account_meta_from_offset(offset),
+            Err(TieredStorageError::OffsetOutOfBounds(160, 160))
+        );
     }
 
     #[test]
@@ -1270,6 +2319,12 @@ pub mod tests {
             hot_storage.get_account(IndexOffset(NUM_ACCOUNTS as u32)),
             Ok(None)
         );
+        // Offsets beyond the end of the index block are rejected.
+        assert_matches!(
+            hot_storage.get_account(IndexOffset(NUM_ACCOUNTS as u32 + 5)),
+            Err(TieredStorageError::IndexOffsetOutOfBounds(offset, count))
+                if offset == NUM_ACCOUNTS as u32 + 5 && count == NUM_ACCOUNTS as u32
+        );
     }
 
     #[test]
@@ -1286,6 +2341,417 @@ pub mod tests {
         assert_matches!(HotStorageWriter::new(&path), Err(_));
     }
 
+    #[test]
+    fn test_hot_storage_writer_with_capacity() {
+        const NUM_ACCOUNTS: u64 = 10_000;
+        let accounts: Vec<_> = (1..=NUM_ACCOUNTS)
+            .map(|seed| create_test_account(seed % 100 + 1))
+            .collect();
+        let hashes = new_account_hashes(accounts.len());
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir
+            .path()
+            .join("test_hot_storage_writer_without_capacity");
+        let path_with_capacity = temp_dir
+            .path()
+            .join("test_hot_storage_writer_with_capacity");
+        let stored_infos = write_hot_accounts_with_hashes(
+            HotStorageWriter::new(&path).unwrap(),
+            Slot::MAX,
+            &accounts,
+            &hashes,
+        );
+        let stored_infos_with_capacity = write_hot_accounts_with_hashes(
+            HotStorageWriter::with_capacity(&path_with_capacity, accounts.len()).unwrap(),
+            Slot::MAX,
+            &accounts,
+            &hashes,
+        );
+
+        // the capacity hint must not change what is written
+        assert!(stored_infos
+            .iter()
+            .zip(&stored_infos_with_capacity)
+            .all(|(a, b)| a.offset == b.offset && a.size == b.size));
+        // Apart from the footer, which holds a placeholder hash, the files
+        // are identical.
+        let bytes = std::fs::read(&path).unwrap();
+        let bytes_with_capacity = std::fs::read(&path_with_capacity).unwrap();
+        assert_eq!(bytes.len(), bytes_with_capacity.len());
+        assert_eq!(
+            bytes[..bytes.len() - FOOTER_SIZE],
+            bytes_with_capacity[..bytes.len() - FOOTER_SIZE]
+        );
+
+        let hot_storage = HotStorageReader::new_from_path(&path).unwrap();
+        let hot_storage_with_capacity =
+            HotStorageReader::new_from_path(&path_with_capacity).unwrap();
+        assert_eq!(
+            TieredStorageFooter {
+                hash: Hash::default(),
+                ..*hot_storage.footer()
+            },
+            TieredStorageFooter {
+                hash: Hash::default(),
+                ..*hot_storage_with_capacity.footer()
+            },
+        );
+
+        assert_eq!(hot_storage_with_capacity.num_accounts(), accounts.len());
+        for (i, (account, account_hash)) in accounts.iter().zip(&hashes).enumerate() {
+            verify_test_account(
+                &hot_storage_with_capacity,
+                IndexOffset(i as u32),
+                account,
+                account_hash,
+            );
+        }
+    }
+
+    #[test]
+    fn test_hot_storage_write_accounts_iter() {
+        let accounts: Vec<_> = [1, 2, 3, 4, 0, 1000, 2000, 9, 0, 10_000]
+            .into_iter()
+            .map(create_test_account)
+            .collect();
+        let hashes = new_account_hashes(accounts.len());
+        let slot = 42;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test_hot_storage_write_accounts_iter");
+        let stored_infos = HotStorageWriter::new(&path)
+            .unwrap()
+            .write_accounts_iter(
+                accounts
+                    .iter()
+                    .zip(&hashes)
+                    .map(|((stored_meta, account), account_hash)| {
+                        (
+                            &stored_meta.pubkey,
+                            account,
+                            account_hash,
+                            stored_meta.write_version_obsolete,
+                        )
+                    }),
+                slot,
+            )
+            .unwrap();
+
+        let slice_path = temp_dir
+            .path()
+            .join("test_hot_storage_write_accounts_slice");
+        let slice_stored_infos = write_hot_accounts_with_hashes(
+            HotStorageWriter::new(&slice_path).unwrap(),
+            slot,
+            &accounts,
+            &hashes,
+        );
+
+        assert_eq!(stored_infos.len(), slice_stored_infos.len());
+        assert!(stored_infos
+            .iter()
+            .zip(&slice_stored_infos)
+            .all(|(a, b)| a.offset == b.offset && a.size == b.size));
+        // Apart from the placeholder hash in the footer, the files are
+        // identical.
+        let bytes = std::fs::read(&path).unwrap();
+        let slice_bytes = std::fs::read(&slice_path).unwrap();
+        assert_eq!(bytes.len(), slice_bytes.len());
+        assert!(bytes[..bytes.len() - FOOTER_SIZE] == slice_bytes[..bytes.len() - FOOTER_SIZE]);
+
+        let hot_storage = HotStorageReader::new_from_path(&path).unwrap();
+        let slice_hot_storage = HotStorageReader::new_from_path(&slice_path).unwrap();
+        assert_eq!(
+            TieredStorageFooter {
+                hash: Hash::default(),
+                ..*hot_storage.footer()
+            },
+            TieredStorageFooter {
+                hash: Hash::default(),
+                ..*slice_hot_storage.footer()
+            },
+        );
+        assert_eq!(hot_storage.slot(), slot);
+        assert_eq!(hot_storage.num_accounts(), accounts.len());
+        for (i, (account, account_hash)) in accounts.iter().zip(&hashes).enumerate() {
+            verify_test_account(&hot_storage, IndexOffset(i as u32), account, account_hash);
+        }
+    }
+
+    #[test]
+    fn test_hot_storage_sorted_index() {
+        let accounts: Vec<_> = [1, 2, 3, 4, 0, 1000, 2000, 9, 0, 10_000, 5, 6, 7]
+            .into_iter()
+            .map(create_test_account)
+            .collect();
+        let hashes = new_account_hashes(accounts.len());
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test_hot_storage_sorted_index");
+        let stored_infos = write_hot_accounts_with_hashes(
+            HotStorageWriter::new(&path).unwrap().with_sorted_index(),
+            Slot::MAX,
+            &accounts,
+            &hashes,
+        );
+        let unsorted_path = temp_dir.path().join("test_hot_storage_unsorted_index");
+        write_hot_accounts_with_hashes(
+            HotStorageWriter::new(&unsorted_path).unwrap(),
+            Slot::MAX,
+            &accounts,
+            &hashes,
+        );
+
+        let hot_storage = HotStorageReader::new_from_path(&path).unwrap();
+        let unsorted_hot_storage = HotStorageReader::new_from_path(&unsorted_path).unwrap();
+        assert!(hot_storage.footer().index_sorted());
+        assert!(!unsorted_hot_storage.footer().index_sorted());
+        hot_storage.validate_index().unwrap();
+
+        // only the index block is reordered, the account blocks are not
+        let account_blocks_len = hot_storage.footer().index_block_offset as usize;
+        assert_eq!(
+            account_blocks_len,
+            unsorted_hot_storage.footer().index_block_offset as usize
+        );
+        assert_eq!(
+            hot_storage.mmap[..account_blocks_len],
+            unsorted_hot_storage.mmap[..account_blocks_len]
+        );
+
+        // the addresses are in increasing order
+        let addresses: Vec<_> = (0..accounts.len())
+            .map(|i| {
+                *hot_storage
+                    .get_account_address(IndexOffset(i as u32))
+                    .unwrap()
+            })
+            .collect();
+        assert!(addresses.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // each StoredAccountInfo points to its own account
+        for (i, stored_info) in stored_infos.iter().enumerate() {
+            verify_test_account(
+                &hot_storage,
+                IndexOffset(stored_info.offset as u32),
+                &accounts[i],
+                &hashes[i],
+            );
+        }
+
+        // lookups are binary searches
+        let max_probes = u64::from((accounts.len() as u32).ilog2() + 1);
+        for (i, stored_info) in stored_infos.iter().enumerate() {
+            let probes = hot_storage.index_probes.load(Ordering::Relaxed);
+            assert_eq!(
+                hot_storage
+                    .find_index_offset(&accounts[i].0.pubkey)
+                    .unwrap(),
+                Some(IndexOffset(stored_info.offset as u32))
+            );
+            assert!(hot_storage.index_probes.load(Ordering::Relaxed) - probes <= max_probes);
+        }
+        let probes = hot_storage.index_probes.load(Ordering::Relaxed);
+        assert_eq!(
+            hot_storage
+                .find_index_offset(&Pubkey::new_unique())
+                .unwrap(),
+            None
+        );
+        assert!(hot_storage.index_probes.load(Ordering::Relaxed) - probes <= max_probes);
+    }
+
+    /// Writes test accounts with `writer`, where the account at
+    /// `duplicate_position` reuses the address of the first one, and
+    /// returns the result along with the writer and the accounts.
+    fn write_accounts_with_duplicate(
+        writer: HotStorageWriter,
+        duplicate_position: usize,
+    ) -> (
+        TieredStorageResult<Vec<StoredAccountInfo>>,
+        HotStorageWriter,
+        Vec<(StoredMeta, AccountSharedData)>,
+    ) {
+        let mut accounts: Vec<_> = [1, 2, 3, 4, 1000, 2000]
+            .into_iter()
+            .map(create_test_account)
+            .collect();
+        accounts[duplicate_position].0.pubkey = accounts[0].0.pubkey;
+        let hashes = new_account_hashes(accounts.len());
+        let result = with_storable_accounts(Slot::MAX, &accounts, &hashes, |storable_accounts| {
+            writer.write_accounts(storable_accounts, 0)
+        });
+        (result, writer, accounts)
+    }
+
+    #[test]
+    fn test_hot_storage_duplicate_pubkey() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test_hot_storage_duplicate_pubkey");
+        let (result, _writer, accounts) =
+            write_accounts_with_duplicate(HotStorageWriter::new(path).unwrap(), 3);
+        assert_matches!(
+            result,
+            Err(TieredStorageError::DuplicatePubkey(address)) if address == accounts[0].0.pubkey
+        );
+    }
+
+    #[test]
+    fn test_hot_storage_collapse_duplicates() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test_hot_storage_collapse_duplicates");
+        let (result, writer, accounts) = write_accounts_with_duplicate(
+            HotStorageWriter::new(&path)
+                .unwrap()
+                .with_duplicates_collapsed(),
+            3,
+        );
+        let stored_infos = result.unwrap();
+        assert_eq!(writer.collapsed_duplicates(), 1);
+        assert_eq!(stored_infos.len(), accounts.len());
+
+        let hot_storage = HotStorageReader::new_from_path(&path).unwrap();
+        assert_eq!(hot_storage.num_accounts(), accounts.len() - 1);
+        hot_storage.validate_index().unwrap();
+
+        // the first account is dropped in favor of the last one
+        assert_eq!(stored_infos[0].size, 0);
+        assert_eq!(stored_infos[0].offset, stored_infos[3].offset);
+        assert!(stored_infos[3].size > 0);
+        for (stored_info, (stored_meta, account)) in stored_infos.iter().zip(&accounts).skip(1) {
+            let (stored_account, _) = hot_storage
+                .get_account(IndexOffset(stored_info.offset as u32))
+                .unwrap()
+                .unwrap();
+            assert_eq!(stored_account.pubkey(), &stored_meta.pubkey);
+            assert_eq!(stored_account.lamports(), account.lamports());
+            assert_eq!(stored_account.data(), account.data());
+        }
+    }
+
+    #[test]
+    fn test_hot_storage_owners_table() {
+        // repeated sizes make some accounts share the same owner
+        let account_data_sizes = &[1, 2, 3, 4, 5, 1000, 5, 4, 3, 2, 1];
+
+        let accounts: Vec<_> = account_data_sizes
+            .iter()
+            .map(|size| create_test_account(*size))
+            .collect();
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test_hot_storage_owners_table");
+        write_hot_accounts(HotStorageWriter::new(&path).unwrap(), &accounts);
+
+        let hot_storage = HotStorageReader::new_from_path(&path).unwrap();
+
+        // owners are assigned offsets in the order they first appear
+        let mut expected_owners: Vec<Pubkey> = vec![];
+        for (_, account) in &accounts {
+            if !expected_owners.contains(account.owner()) {
+                expected_owners.push(*account.owner());
+            }
+        }
+        let owners_table = hot_storage.owners_table().unwrap();
+        assert_eq!(owners_table.len(), hot_storage.num_owners());
+        assert!(owners_table.len() < accounts.len());
+        assert_eq!(owners_table, expected_owners);
+    }
+
+    #[test]
+    fn test_hot_storage_accounts_by_owner() {
+        let program_id = Pubkey::new_unique();
+        let mut accounts: Vec<_> = (1..=10).map(create_test_account).collect();
+        for (i, (_, account)) in accounts.iter_mut().enumerate() {
+            account.set_owner(if i % 2 == 0 {
+                program_id
+            } else {
+                system_program::id()
+            });
+        }
+        // a zero-lamport account is stored with the OWNER_NO_OWNER placeholder
+        accounts.push(create_test_account(0));
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test_hot_storage_accounts_by_owner");
+        write_hot_accounts(HotStorageWriter::new(&path).unwrap(), &accounts);
+        let hot_storage = HotStorageReader::new_from_path(&path).unwrap();
+
+        for owner in [program_id, system_program::id()] {
+            let expected_addresses: Vec<_> = accounts
+                .iter()
+                .filter(|(_, account)| account.lamports() > 0 && *account.owner() == owner)
+                .map(|(stored_meta, _)| stored_meta.pubkey)
+                .collect();
+            assert_eq!(expected_addresses.len(), 5);
+
+            let stored_metas = hot_storage.accounts_by_owner(&owner).unwrap();
+            assert!(stored_metas
+                .iter()
+                .all(|stored_meta| *stored_meta.owner() == owner));
+            let addresses: Vec<_> = stored_metas
+                .iter()
+                .map(|stored_meta| *stored_meta.pubkey())
+                .collect();
+            assert_eq!(addresses, expected_addresses);
+        }
+
+        // the zero-lamport account is never returned, even though the
+        // OWNER_NO_OWNER placeholder is the system program address
+        assert_eq!(*OWNER_NO_OWNER, system_program::id());
+        assert!(hot_storage
+            .accounts_by_owner(&OWNER_NO_OWNER)
+            .unwrap()
+            .iter()
+            .all(|stored_meta| stored_meta.lamports() > 0));
+
+        assert!(hot_storage
+            .accounts_by_owner(&Pubkey::new_unique())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_hot_storage_accounts_not_owned_by() {
+        let mut accounts: Vec<_> = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 1000, 2000]
+            .iter()
+            .map(|size| create_test_account(*size))
+            .collect();
+        for (_, account) in accounts.iter_mut().step_by(3) {
+            account.set_owner(system_program::id());
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir
+            .path()
+            .join("test_hot_storage_accounts_not_owned_by");
+        write_hot_accounts(HotStorageWriter::new(&path).unwrap(), &accounts);
+        let hot_storage = HotStorageReader::new_from_path(&path).unwrap();
+
+        let excluded_owners = HashSet::from([system_program::id()]);
+        let expected_addresses: Vec<_> = accounts
+            .iter()
+            .filter(|(_, account)| *account.owner() != system_program::id())
+            .map(|(stored_meta, _)| stored_meta.pubkey)
+            .collect();
+        let addresses: Vec<_> = hot_storage
+            .accounts_not_owned_by(&excluded_owners)
+            .map(|stored_meta| {
+                let stored_meta = stored_meta.unwrap();
+                assert_ne!(*stored_meta.owner(), system_program::id());
+                *stored_meta.pubkey()
+            })
+            .collect();
+        assert_eq!(addresses.len(), 8);
+        assert_eq!(addresses, expected_addresses);
+
+        // an empty set excludes nothing
+        assert_eq!(
+            hot_storage.accounts_not_owned_by(&HashSet::new()).count(),
+            accounts.len()
+        );
+    }
+
     /// Create a test account based on the specified seed.
     /// The created test account might have default rent_epoch
     /// and write_version.
@@ -1347,6 +2813,20 @@ pub mod tests {
         );
     }
 
+    /// Verifies the account at `index_offset` of `hot_storage` against the
+    /// test account it was written from with `account_hash`.
+    fn verify_test_account(
+        hot_storage: &HotStorageReader,
+        index_offset: IndexOffset,
+        (stored_meta, account): &(StoredMeta, AccountSharedData),
+        account_hash: &AccountHash,
+    ) {
+        let (stored_account, _) = hot_storage.get_account(index_offset).unwrap().unwrap();
+        // zero-lamport accounts are stored as default accounts
+        let account = (account.lamports() > 0).then_some(account);
+        verify_account(&stored_account, account, &stored_meta.pubkey, account_hash);
+    }
+
     #[test]
     fn test_write_account_and_index_blocks() {
         let account_data_sizes = &[
@@ -1437,4 +2917,110 @@ pub mod tests {
             assert_eq!(&partial_accounts, &accounts[i..]);
         }
     }
+
+    /// A reader that returns at most `chunk_size` bytes per read() call.
+    struct ChunkedReader<'a> {
+        data: &'a [u8],
+        chunk_size: usize,
+    }
+
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.chunk_size).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_hot_storage_write_account_streaming() {
+        let data_len = MAX_PERMITTED_DATA_LENGTH as usize;
+        let account = AccountSharedData::from(Account {
+            lamports: 12345,
+            data: (0..data_len).map(|i| (i % 251) as u8).collect(),
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 7,
+        });
+        let address = Pubkey::new_unique();
+        let account_hash = AccountHash(Hash::new_unique());
+        let meta = StreamingAccountMeta {
+            address: &address,
+            lamports: account.lamports(),
+            owner: account.owner(),
+            executable: account.executable(),
+            rent_epoch: account.rent_epoch(),
+            account_hash: &account_hash,
+        };
+
+        let slot = 42;
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir
+            .path()
+            .join("test_hot_storage_write_account_streaming");
+        let reader = ChunkedReader {
+            data: account.data(),
+            chunk_size: 4096 + 3,
+        };
+        let stored_info = HotStorageWriter::new(&path)
+            .unwrap()
+            .write_account_streaming(&meta, reader, data_len, slot)
+            .unwrap();
+        assert_eq!(stored_info.offset, 0);
+
+        let hot_storage = HotStorageReader::new_from_path(&path).unwrap();
+        assert_eq!(hot_storage.num_accounts(), 1);
+        assert_eq!(hot_storage.footer().slot, slot);
+        let (stored_meta, next) = hot_storage.get_account(IndexOffset(0)).unwrap().unwrap();
+        assert_eq!(next, IndexOffset(1));
+        verify_account(&stored_meta, Some(&account), &address, &account_hash);
+        assert_eq!(stored_meta.rent_epoch(), account.rent_epoch());
+        assert_eq!(hot_storage.get_account(IndexOffset(1)).unwrap(), None);
+
+        // the streamed file is laid out exactly as write_accounts() does
+        let regular_path = temp_dir
+            .path()
+            .join("test_hot_storage_write_account_regular");
+        let stored_meta = StoredMeta {
+            write_version_obsolete: u64::MAX,
+            data_len: data_len as u64,
+            pubkey: address,
+        };
+        let regular_infos = write_hot_accounts_with_hashes(
+            HotStorageWriter::new(&regular_path).unwrap(),
+            slot,
+            &[(stored_meta, account.clone())],
+            &[account_hash],
+        );
+        assert_eq!(regular_infos[0].size, stored_info.size);
+        let bytes = std::fs::read(&path).unwrap();
+        let regular_bytes = std::fs::read(&regular_path).unwrap();
+        assert_eq!(bytes.len(), regular_bytes.len());
+        assert!(bytes[..bytes.len() - FOOTER_SIZE] == regular_bytes[..bytes.len() - FOOTER_SIZE]);
+        // only the footer hash, which is unique to each file, may differ
+        let regular_footer = *HotStorageReader::new_from_path(&regular_path)
+            .unwrap()
+            .footer();
+        assert_eq!(
+            TieredStorageFooter {
+                hash: regular_footer.hash,
+                ..*hot_storage.footer()
+            },
+            regular_footer
+        );
+
+        // a reader that runs out of data early is reported as an error
+        let short_path = temp_dir.path().join("test_hot_storage_write_account_short");
+        let reader = ChunkedReader {
+            data: &account.data()[..100],
+            chunk_size: 16,
+        };
+        assert_matches!(
+            HotStorageWriter::new(short_path)
+                .unwrap()
+                .write_account_streaming(&meta, reader, data_len, slot),
+            Err(TieredStorageError::Io(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof
+        );
+    }
 }
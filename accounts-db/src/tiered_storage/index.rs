@@ -43,12 +43,19 @@ const _: () = assert!(std::mem::size_of::<IndexOffset>() == 4);
     num_enum::IntoPrimitive,
     num_enum::TryFromPrimitive,
 )]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum IndexBlockFormat {
     /// This format optimizes the storage size by storing only account addresses
     /// and block offsets.  It skips storing the size of account data by storing
     /// account block entries and index block entries in the same order.
     #[default]
     AddressesThenOffsets = 0,
+    /// The same layout as AddressesThenOffsets, but the entries are sorted
+    /// by address, which allows an address to be looked up with a binary
+    /// search.  The account blocks keep the order they were written in, so
+    /// the size of an account block is derived from the next larger offset
+    /// instead of the offset of the next entry.
+    SortedAddressesThenOffsets = 1,
 }
 
 // Ensure there are no implicit padding bytes
@@ -63,7 +70,7 @@ impl IndexBlockFormat {
         index_entries: &[AccountIndexWriterEntry<impl AccountOffset>],
     ) -> TieredStorageResult<usize> {
         match self {
-            Self::AddressesThenOffsets => {
+            Self::AddressesThenOffsets | Self::SortedAddressesThenOffsets => {
                 let mut bytes_written = 0;
                 for index_entry in index_entries {
                     bytes_written += file.write_pod(index_entry.address)?;
@@ -84,7 +91,7 @@ impl IndexBlockFormat {
         index_offset: IndexOffset,
     ) -> TieredStorageResult<&'a Pubkey> {
         let offset = match self {
-            Self::AddressesThenOffsets => {
+            Self::AddressesThenOffsets | Self::SortedAddressesThenOffsets => {
                 debug_assert!(index_offset.0 < footer.account_entry_count);
                 footer.index_block_offset as usize
                     + std::mem::size_of::<Pubkey>() * (index_offset.0 as usize)
@@ -111,7 +118,7 @@ impl IndexBlockFormat {
         index_offset: IndexOffset,
     ) -> TieredStorageResult<Offset> {
         let offset = match self {
-            Self::AddressesThenOffsets => {
+            Self::AddressesThenOffsets | Self::SortedAddressesThenOffsets => {
                 debug_assert!(index_offset.0 < footer.account_entry_count);
                 footer.index_block_offset as usize
                     + std::mem::size_of::<Pubkey>() * footer.account_entry_count as usize
@@ -135,11 +142,19 @@ impl IndexBlockFormat {
     /// Returns the size of one index entry.
     pub fn entry_size<Offset: AccountOffset>(&self) -> usize {
         match self {
-            Self::AddressesThenOffsets => {
+            Self::AddressesThenOffsets | Self::SortedAddressesThenOffsets => {
                 std::mem::size_of::<Pubkey>() + std::mem::size_of::<Offset>()
             }
         }
     }
+
+    /// Returns true if the index entries are sorted by address.
+    pub fn is_sorted(&self) -> bool {
+        match self {
+            Self::AddressesThenOffsets => false,
+            Self::SortedAddressesThenOffsets => true,
+        }
+    }
 }
 
 #[cfg(test)]
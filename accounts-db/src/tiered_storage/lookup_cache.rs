@@ -0,0 +1,136 @@
+//! A small LRU cache of resolved account addresses for tiered storage readers.
+
+use {
+    crate::tiered_storage::index::IndexOffset,
+    index_list::{Index, IndexList},
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        },
+    },
+};
+
+#[derive(Debug, Default)]
+struct LookupCacheEntries {
+    /// The resolved IndexOffset of each cached address, together with the
+    /// index of the address in the eviction queue.
+    offsets: HashMap<Pubkey, (IndexOffset, Index)>,
+    /// Cached addresses ordered from the least to the most recently used.
+    queue: IndexList<Pubkey>,
+}
+
+/// A thread-safe LRU cache mapping account addresses to their IndexOffset,
+/// which allows repeated lookups of the same address to skip scanning the
+/// index block.
+#[derive(Debug)]
+pub struct LookupCache {
+    capacity: usize,
+    entries: Mutex<LookupCacheEntries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl LookupCache {
+    /// Creates an empty cache holding at most `capacity` addresses.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::default(),
+            hits: AtomicU64::default(),
+            misses: AtomicU64::default(),
+        }
+    }
+
+    /// Returns the maximum number of addresses held by the cache.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of addresses currently held by the cache.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of lookups answered by the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of lookups not answered by the cache.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the cached IndexOffset of `address` and marks it as the most
+    /// recently used entry.
+    pub(crate) fn get(&self, address: &Pubkey) -> Option<IndexOffset> {
+        let mut entries = self.entries.lock().unwrap();
+        let LookupCacheEntries { offsets, queue } = &mut *entries;
+        let Some((index_offset, queue_index)) = offsets.get_mut(address) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        queue.remove(*queue_index);
+        *queue_index = queue.insert_last(*address);
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(*index_offset)
+    }
+
+    /// Caches the resolved IndexOffset of `address`, evicting the least
+    /// recently used entries when the cache is over capacity.
+    pub(crate) fn insert(&self, address: &Pubkey, index_offset: IndexOffset) {
+        let mut entries = self.entries.lock().unwrap();
+        let LookupCacheEntries { offsets, queue } = &mut *entries;
+        if let Some((_, queue_index)) = offsets.get(address) {
+            queue.remove(*queue_index);
+        }
+        let queue_index = queue.insert_last(*address);
+        offsets.insert(*address, (index_offset, queue_index));
+        while offsets.len() > self.capacity {
+            let Some(evicted) = queue.remove_first() else {
+                break;
+            };
+            offsets.remove(&evicted);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_cache_eviction() {
+        let cache = LookupCache::new(2);
+        let addresses: Vec<_> = std::iter::repeat_with(Pubkey::new_unique).take(3).collect();
+
+        cache.insert(&addresses[0], IndexOffset(0));
+        cache.insert(&addresses[1], IndexOffset(1));
+        // touch addresses[0] so that addresses[1] becomes the LRU entry
+        assert_eq!(cache.get(&addresses[0]), Some(IndexOffset(0)));
+        cache.insert(&addresses[2], IndexOffset(2));
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&addresses[1]), None);
+        assert_eq!(cache.get(&addresses[0]), Some(IndexOffset(0)));
+        assert_eq!(cache.get(&addresses[2]), Some(IndexOffset(2)));
+        assert_eq!(cache.hits(), 3);
+        assert_eq!(cache.misses(), 1);
+    }
+
+    #[test]
+    fn test_lookup_cache_zero_capacity() {
+        let cache = LookupCache::new(0);
+        let address = Pubkey::new_unique();
+        cache.insert(&address, IndexOffset(0));
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(&address), None);
+    }
+}
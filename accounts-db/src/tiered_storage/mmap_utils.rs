@@ -32,6 +32,44 @@ pub unsafe fn get_type<T>(mmap: &Mmap, offset: usize) -> IoResult<(&T, usize)> {
     Ok((unsafe { &*ptr }, next))
 }
 
+/// Advises the kernel that the `size` bytes at `offset` of `mmap` will be
+/// accessed soon, so that their pages can be read ahead.
+///
+/// This is only a hint: the range is clamped to the mmap, failures are
+/// ignored, and it does nothing on non-unix platforms.
+pub fn prefetch(mmap: &Mmap, offset: usize, size: usize) {
+    #[cfg(unix)]
+    {
+        let size = size.min(mmap.len().saturating_sub(offset));
+        if size > 0 {
+            if let Err(err) = mmap.advise_range(memmap2::Advice::WillNeed, offset, size) {
+                debug!("Failed to prefetch offset {offset} and size {size}: {err}");
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (mmap, offset, size);
+}
+
+/// Reads one byte of every page of the `size` bytes at `offset` of `mmap`,
+/// so that the pages are resident once it returns.  Unlike prefetch(), this
+/// blocks on the page faults, so it should only be called from a thread
+/// that is allowed to block.  The range is clamped to the mmap.
+pub fn touch_pages(mmap: &Mmap, offset: usize, size: usize) {
+    const PAGE_SIZE: usize = 4096;
+    let end = offset.saturating_add(size).min(mmap.len());
+    for byte in mmap
+        .get(offset..end)
+        .unwrap_or_default()
+        .iter()
+        .step_by(PAGE_SIZE)
+    {
+        // SAFETY: the byte is a valid reference into the mmap; the volatile
+        // read only prevents the compiler from eliding the access.
+        unsafe { std::ptr::read_volatile(byte) };
+    }
+}
+
 /// Get a reference to the data at `offset` of `size` bytes if that slice
 /// doesn't overrun the internal buffer. Otherwise return an Error.
 /// Also return the offset of the first byte after the requested data that
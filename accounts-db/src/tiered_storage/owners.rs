@@ -1,6 +1,8 @@
 use {
     crate::tiered_storage::{
-        file::TieredStorageFile, footer::TieredStorageFooter, mmap_utils::get_pod,
+        file::TieredStorageFile,
+        footer::TieredStorageFooter,
+        mmap_utils::{get_pod, get_slice},
         TieredStorageResult,
     },
     indexmap::set::IndexSet,
@@ -35,6 +37,7 @@ lazy_static! {
     num_enum::IntoPrimitive,
     num_enum::TryFromPrimitive,
 )]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum OwnersBlockFormat {
     /// This format persists OwnerBlock as a consecutive bytes of pubkeys
     /// without any meta-data.  For each account meta, it has a owner_offset
@@ -82,6 +85,52 @@ impl OwnersBlockFormat {
     }
 }
 
+/// A lightweight read-only view of the owners block of a tiered accounts
+/// file, which only requires the footer and the owners block to be parsed.
+#[derive(Debug)]
+pub struct OwnersView {
+    mmap: Mmap,
+    footer: TieredStorageFooter,
+}
+
+impl OwnersView {
+    /// Creates an OwnersView over `mmap`, whose footer has already been
+    /// validated.  An error is returned if the owners block described by
+    /// the footer does not fit in `mmap`.
+    pub(crate) fn new(mmap: Mmap, footer: TieredStorageFooter) -> TieredStorageResult<Self> {
+        get_slice(
+            &mmap,
+            footer.owners_block_offset as usize,
+            footer.owner_count as usize * std::mem::size_of::<Pubkey>(),
+        )?;
+        Ok(Self { mmap, footer })
+    }
+
+    /// Returns the number of owners in the owners block.
+    pub fn len(&self) -> usize {
+        self.footer.owner_count as usize
+    }
+
+    /// Returns true if the owners block is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the address of the owner at `index`, or None if `index` is
+    /// not less than len().
+    pub fn get(&self, index: usize) -> Option<Pubkey> {
+        if index >= self.len() {
+            return None;
+        }
+        // The owners block has been checked to fit in the mmap on creation.
+        self.footer
+            .owners_block_format
+            .get_owner_address(&self.mmap, &self.footer, OwnerOffset(index as u32))
+            .ok()
+            .copied()
+    }
+}
+
 /// The in-memory representation of owners block for write.
 /// It manages a set of unique addresses of account owners.
 #[derive(Debug, Default)]
@@ -93,6 +142,14 @@ pub struct OwnersTable<'a> {
 /// meta-data.  For each account meta, it has a owner_offset field to
 /// access its owner's address in the OwnersBlock.
 impl<'a> OwnersTable<'a> {
+    /// Creates an empty OwnersTable with space for at least `capacity`
+    /// unique owner addresses.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            owners_set: IndexSet::with_capacity(capacity),
+        }
+    }
+
     /// Add the specified pubkey as the owner into the OwnersWriterTable
     /// if the specified pubkey has not existed in the OwnersWriterTable
     /// yet.  In any case, the function returns its OwnerOffset.
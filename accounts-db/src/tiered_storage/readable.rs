@@ -1,18 +1,30 @@
 use {
     crate::{
-        account_storage::meta::StoredAccountMeta,
+        account_storage::meta::{StoredAccountInfo, StoredAccountMeta},
+        accounts_db::AccountsDb,
         accounts_file::MatchAccountOwnerError,
         accounts_hash::AccountHash,
         tiered_storage::{
-            footer::{AccountMetaFormat, TieredStorageFooter},
+            file::TieredStorageFile,
+            footer::{AccountMetaFormat, TieredStorageFooter, FOOTER_SIZE_V1},
             hot::HotStorageReader,
             index::IndexOffset,
+            lookup_cache::LookupCache,
             meta::TieredAccountMeta,
-            TieredStorageResult,
+            owners::OwnersView,
+            TieredStorageError, TieredStorageResult,
         },
     },
-    solana_sdk::{account::ReadableAccount, pubkey::Pubkey, stake_history::Epoch},
-    std::path::Path,
+    memmap2::MmapOptions,
+    solana_sdk::{
+        account::ReadableAccount, clock::Slot, hash::Hash, pubkey::Pubkey, stake_history::Epoch,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        fs::File,
+        io::Write,
+        path::Path,
+    },
 };
 
 /// The struct that offers read APIs for accessing a TieredAccount.
@@ -86,6 +98,65 @@ impl<'accounts_file, M: TieredAccountMeta> ReadableAccount
     }
 }
 
+/// The intermediate state for computing the hash of the accounts inside a
+/// tiered storage file in resumable chunks.
+///
+/// See TieredStorageReader::hash_chunk().
+#[derive(Debug, Default)]
+pub struct HashState {
+    hasher: blake3::Hasher,
+}
+
+impl HashState {
+    /// Folds the specified account address and its account hash into
+    /// the state.
+    fn update(&mut self, address: &Pubkey, account_hash: &AccountHash) {
+        self.hasher.update(address.as_ref());
+        self.hasher.update(account_hash.0.as_ref());
+    }
+
+    /// Returns the hash of all the accounts that have been folded into
+    /// this state.
+    pub fn finalize(&self) -> Hash {
+        Hash::new_from_array(self.hasher.finalize().into())
+    }
+}
+
+/// The statistics of the accounts inside a tiered storage file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TieredStorageStats {
+    /// The number of accounts.
+    pub num_accounts: usize,
+    /// The number of unique account owners after deduplication in the
+    /// owners block.
+    pub num_unique_owners: usize,
+}
+
+/// A summary of how efficiently a tiered storage file stores its accounts.
+///
+/// The bytes of the file are split into three disjoint categories so that
+/// `overhead_bytes + data_bytes + slack_bytes == file_size`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EfficiencyReport {
+    /// The size of the file in bytes.
+    pub file_size: usize,
+    /// The number of bytes storing account data.
+    pub data_bytes: usize,
+    /// The number of padding bytes used for alignment.
+    pub slack_bytes: usize,
+    /// The number of bytes storing anything other than account data or
+    /// padding, i.e. account metas, optional fields, index, owners and footer.
+    pub overhead_bytes: usize,
+    /// The ratio of overhead_bytes to file_size.
+    pub overhead_ratio: f64,
+    /// The ratio of the uncompressed size of the account blocks to their
+    /// stored size.
+    pub compression_ratio: f64,
+    /// The number of bytes saved by storing each unique owner address once
+    /// instead of once per account.
+    pub dedup_savings_bytes: usize,
+}
+
 /// The reader of a tiered storage instance.
 #[derive(Debug)]
 pub enum TieredStorageReader {
@@ -94,14 +165,105 @@ pub enum TieredStorageReader {
 
 impl TieredStorageReader {
     /// Creates a reader for the specified tiered storage accounts file.
+    ///
+    /// TieredStorageError::FileTooSmall will be returned, before attempting
+    /// to parse the footer, if the file cannot even hold a footer.
     pub fn new_from_path(path: impl AsRef<Path>) -> TieredStorageResult<Self> {
+        let file_size = std::fs::metadata(&path)?.len();
+        if file_size < FOOTER_SIZE_V1 as u64 {
+            return Err(TieredStorageError::FileTooSmall(
+                path.as_ref().to_path_buf(),
+                file_size,
+            ));
+        }
+
         let footer = TieredStorageFooter::new_from_path(&path)?;
         match footer.account_meta_format {
             AccountMetaFormat::Hot => Ok(Self::Hot(HotStorageReader::new_from_path(path)?)),
         }
     }
 
-    /// Returns the footer of the associated HotAccountsFile.
+    /// Same as new_from_path(), but tolerates corrupt entries in the index
+    /// block, which are skipped when iterating the accounts.  Returns the
+    /// reader together with the non-fatal errors found in the index block,
+    /// which is empty for an intact file.  The footer must still be valid.
+    pub fn new_best_effort_from_path(
+        path: impl AsRef<Path>,
+    ) -> TieredStorageResult<(Self, Vec<TieredStorageError>)> {
+        match Self::new_from_path(path)? {
+            Self::Hot(hot) => {
+                let (hot, errors) = hot.with_corrupt_entries_skipped()?;
+                Ok((Self::Hot(hot), errors))
+            }
+        }
+    }
+
+    /// Creates an OwnersView of the tiered storage file at `path`, which only
+    /// maps the file and parses its footer and owners block.  This is a
+    /// cheaper handle than a full reader for tools that only need owners.
+    ///
+    /// The footer, including the magic number, is validated the same way
+    /// as in new_from_path().
+    pub fn new_owners_only_from_path(path: impl AsRef<Path>) -> TieredStorageResult<OwnersView> {
+        let file = File::open(&path)?;
+        let file_size = file.metadata()?.len();
+        if file_size < FOOTER_SIZE_V1 as u64 {
+            return Err(TieredStorageError::FileTooSmall(
+                path.as_ref().to_path_buf(),
+                file_size,
+            ));
+        }
+
+        let mmap = unsafe { MmapOptions::new().map(&file)? };
+        let footer = TieredStorageFooter::new_from_mmap(&mmap)?;
+        OwnersView::new(mmap, footer)
+    }
+
+    /// Creates a reader from an already-opened tiered storage file.
+    ///
+    /// The file is consumed and memory-mapped as-is; its current seek
+    /// position is not preserved.
+    pub fn new_from_file(file: File) -> TieredStorageResult<Self> {
+        let file = TieredStorageFile(file);
+        let footer = TieredStorageFooter::new_from_footer_block(&file)?;
+        match footer.account_meta_format {
+            AccountMetaFormat::Hot => Ok(Self::Hot(HotStorageReader::new_from_file(file.0)?)),
+        }
+    }
+
+    /// Enables an LRU cache of up to `capacity` addresses resolved by
+    /// find_index_offset(), so that repeated lookups of the same address
+    /// skip scanning the index block.  The cache is disabled by default.
+    pub fn with_lookup_cache(self, capacity: usize) -> Self {
+        match self {
+            Self::Hot(hot) => Self::Hot(hot.with_lookup_cache(capacity)),
+        }
+    }
+
+    /// Returns the lookup cache, if enabled.
+    pub fn lookup_cache(&self) -> Option<&LookupCache> {
+        match self {
+            Self::Hot(hot) => hot.lookup_cache(),
+        }
+    }
+
+    /// Returns the slot that the underlying file was written for.
+    pub fn slot(&self) -> Slot {
+        match self {
+            Self::Hot(hot) => hot.slot(),
+        }
+    }
+
+    /// Returns the size of the memory-mapped region of the underlying file.
+    pub fn mapped_len(&self) -> u64 {
+        match self {
+            Self::Hot(hot) => hot.mapped_len(),
+        }
+    }
+
+    /// Returns a read-only view of the footer of the underlying tiered
+    /// storage file, which describes the formats and the offsets of each
+    /// block inside the file.
     pub fn footer(&self) -> &TieredStorageFooter {
         match self {
             Self::Hot(hot) => hot.footer(),
@@ -115,7 +277,34 @@ impl TieredStorageReader {
         }
     }
 
+    /// Returns the total number of unique owners.
+    pub fn num_owners(&self) -> usize {
+        match self {
+            Self::Hot(hot) => hot.num_owners(),
+        }
+    }
+
+    /// Returns the TieredStorageStats of the underlying tiered storage file.
+    pub fn stats(&self) -> TieredStorageStats {
+        TieredStorageStats {
+            num_accounts: self.num_accounts(),
+            num_unique_owners: self.num_owners(),
+        }
+    }
+
+    /// Returns the addresses of all the unique owners in the order of
+    /// their OwnerOffset, allowing owner offsets to be resolved without
+    /// reading any account.
+    pub fn owners_table(&self) -> TieredStorageResult<Vec<Pubkey>> {
+        match self {
+            Self::Hot(hot) => hot.owners_table(),
+        }
+    }
+
     /// Returns the account located at the specified index offset.
+    ///
+    /// Returns Ok(None) when the index offset equals the number of accounts,
+    /// and TieredStorageError::IndexOffsetOutOfBounds when it goes beyond.
     pub fn get_account(
         &self,
         index_offset: IndexOffset,
@@ -125,6 +314,201 @@ impl TieredStorageReader {
         }
     }
 
+    /// Same as get_account(), but without blocking the async executor on
+    /// the page faults of the account block.  Must be called from within a
+    /// tokio runtime.
+    #[cfg(feature = "async")]
+    pub async fn get_account_async(
+        &self,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<Option<(StoredAccountMeta<'_>, IndexOffset)>> {
+        match self {
+            Self::Hot(hot) => hot.get_account_async(index_offset).await,
+        }
+    }
+
+    /// Returns the raw bytes of the `len` bytes at `start` of the account
+    /// blocks region, bounds-checked against the footer.
+    pub fn raw_account_block(&self, start: u64, len: u64) -> TieredStorageResult<&[u8]> {
+        match self {
+            Self::Hot(hot) => hot.raw_account_block(start, len),
+        }
+    }
+
+    /// Returns true if the account of the specified pubkey is stored in the
+    /// file, without materializing the account.
+    pub fn contains(&self, pubkey: &Pubkey) -> TieredStorageResult<bool> {
+        match self {
+            Self::Hot(hot) => hot.contains(pubkey),
+        }
+    }
+
+    /// Returns the hash of the account of the specified pubkey, or None if
+    /// it is not stored in the file, without reading the account data.
+    pub fn get_hash(&self, pubkey: &Pubkey) -> TieredStorageResult<Option<AccountHash>> {
+        match self {
+            Self::Hot(hot) => hot.get_hash(pubkey),
+        }
+    }
+
+    /// Returns the IndexOffset of the account of the specified pubkey, or
+    /// None if it is not stored in the file.
+    pub fn find_index_offset(&self, pubkey: &Pubkey) -> TieredStorageResult<Option<IndexOffset>> {
+        match self {
+            Self::Hot(hot) => hot.find_index_offset(pubkey),
+        }
+    }
+
+    /// Same as get_account(), but in addition prefetches the region of the
+    /// next account while the caller processes the returned one.
+    pub fn get_account_prefetched(
+        &self,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<Option<(StoredAccountMeta<'_>, IndexOffset)>> {
+        match self {
+            Self::Hot(hot) => hot.get_account_prefetched(index_offset),
+        }
+    }
+
+    /// Returns the StoredAccountInfo of the account located at the specified
+    /// index offset, the same as the one returned when it was written.
+    pub fn get_stored_account_info(
+        &self,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<Option<StoredAccountInfo>> {
+        match self {
+            Self::Hot(hot) => hot.get_stored_account_info(index_offset),
+        }
+    }
+
+    /// Returns the accounts of the specified addresses, in the same order as
+    /// the input addresses, with None for the addresses not in this file.
+    pub fn get_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> TieredStorageResult<Vec<Option<StoredAccountMeta<'_>>>> {
+        match self {
+            Self::Hot(hot) => hot.get_accounts(addresses),
+        }
+    }
+
+    /// Returns the accounts owned by the specified owner.
+    pub fn accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+    ) -> TieredStorageResult<Vec<StoredAccountMeta<'_>>> {
+        match self {
+            Self::Hot(hot) => hot.accounts_by_owner(owner),
+        }
+    }
+
+    /// Returns an iterator over the accounts whose owner is not one of the
+    /// specified owners.
+    pub fn accounts_not_owned_by(
+        &self,
+        owners: &HashSet<Pubkey>,
+    ) -> impl Iterator<Item = TieredStorageResult<StoredAccountMeta<'_>>> + '_ {
+        match self {
+            Self::Hot(hot) => hot.accounts_not_owned_by(owners),
+        }
+    }
+
+    /// Writes a human-readable dump of the underlying tiered storage file,
+    /// which includes the footer fields and a summary line per account, into
+    /// the specified writer.
+    pub fn debug_dump(&self, w: &mut impl Write) -> TieredStorageResult<()> {
+        let footer = self.footer();
+        writeln!(w, "format_version: {}", footer.format_version)?;
+        writeln!(w, "account_meta_format: {:?}", footer.account_meta_format)?;
+        writeln!(w, "owners_block_format: {:?}", footer.owners_block_format)?;
+        writeln!(w, "index_block_format: {:?}", footer.index_block_format)?;
+        writeln!(w, "account_block_format: {:?}", footer.account_block_format)?;
+        writeln!(
+            w,
+            "account_meta_entry_size: {}",
+            footer.account_meta_entry_size
+        )?;
+        writeln!(w, "account_block_size: {}", footer.account_block_size)?;
+        writeln!(w, "owner_entry_size: {}", footer.owner_entry_size)?;
+        writeln!(w, "index_block_offset: {}", footer.index_block_offset)?;
+        writeln!(w, "owners_block_offset: {}", footer.owners_block_offset)?;
+        writeln!(w, "min_account_address: {}", footer.min_account_address)?;
+        writeln!(w, "max_account_address: {}", footer.max_account_address)?;
+        writeln!(w, "hash: {}", footer.hash)?;
+        writeln!(w, "footer_size: {}", footer.footer_size)?;
+        writeln!(w, "num_owners: {}", self.num_owners())?;
+        writeln!(w, "num_accounts: {}", self.num_accounts())?;
+
+        let mut index_offset = IndexOffset(0);
+        while let Some((account, next)) = self.get_account(index_offset)? {
+            writeln!(
+                w,
+                "account[{}]: pubkey: {}, lamports: {}, data_len: {}, owner: {}",
+                index_offset.0,
+                account.pubkey(),
+                account.lamports(),
+                account.data_len(),
+                account.owner(),
+            )?;
+            index_offset = next;
+        }
+        Ok(())
+    }
+
+    /// Returns an EfficiencyReport of the underlying tiered storage file.
+    pub fn efficiency_report(&self) -> TieredStorageResult<EfficiencyReport> {
+        match self {
+            Self::Hot(hot) => hot.efficiency_report(),
+        }
+    }
+
+    /// Folds up to `count` accounts starting from `start` into the specified
+    /// HashState, and returns the IndexOffset of the next account to hash.
+    ///
+    /// Folding every account in index order, in any number of chunks,
+    /// results in the same HashState.  Once the returned IndexOffset reaches
+    /// num_accounts(), all the accounts have been hashed.
+    pub fn hash_chunk(
+        &self,
+        state: &mut HashState,
+        start: IndexOffset,
+        count: usize,
+    ) -> TieredStorageResult<IndexOffset> {
+        let mut index_offset = start;
+        for _ in 0..count {
+            let Some((account, next)) = self.get_account(index_offset)? else {
+                break;
+            };
+            state.update(account.pubkey(), account.hash());
+            index_offset = next;
+        }
+        Ok(index_offset)
+    }
+
+    /// Walks the index block, and returns TieredStorageError::CorruptIndex
+    /// if it does not hold `num_accounts()` entries with strictly increasing
+    /// account offsets within the account blocks.
+    pub fn validate_index(&self) -> TieredStorageResult<()> {
+        match self {
+            Self::Hot(hot) => hot.validate_index(),
+        }
+    }
+
+    /// Recomputes the hash of every account from its stored fields, and
+    /// returns the pubkeys of the accounts whose recomputed hash disagrees
+    /// with the stored one.  An empty vector is returned for an intact file.
+    pub fn verify_hashes(&self) -> TieredStorageResult<Vec<Pubkey>> {
+        let mut mismatches = vec![];
+        let mut index_offset = IndexOffset(0);
+        while let Some((account, next)) = self.get_account(index_offset)? {
+            if AccountsDb::hash_account(&account, account.pubkey()) != *account.hash() {
+                mismatches.push(*account.pubkey());
+            }
+            index_offset = next;
+        }
+        Ok(mismatches)
+    }
+
     /// Returns Ok(index_of_matching_owner) if the account owner at
     /// `account_offset` is one of the pubkeys in `owners`.
     ///
@@ -159,4 +543,54 @@ impl TieredStorageReader {
             Self::Hot(hot) => hot.accounts(index_offset),
         }
     }
+
+    /// Invokes `callback` with each account of the file in IndexOffset
+    /// order, without collecting the accounts first.
+    pub fn scan_accounts(
+        &self,
+        mut callback: impl FnMut(&StoredAccountMeta),
+    ) -> TieredStorageResult<()> {
+        let mut index_offset = IndexOffset(0);
+        while let Some((account, next)) = self.get_account(index_offset)? {
+            callback(&account);
+            index_offset = next;
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over the accounts of the file in IndexOffset
+    /// order, each together with its IndexOffset and the byte offset of its
+    /// account block, which can be passed to raw_account_block().
+    pub fn accounts_with_offsets(
+        &self,
+    ) -> impl Iterator<Item = TieredStorageResult<(StoredAccountMeta<'_>, IndexOffset, u64)>> + '_
+    {
+        match self {
+            Self::Hot(hot) => hot.accounts_with_offsets(),
+        }
+    }
+
+    /// Returns all the accounts of the file keyed by their addresses, read
+    /// in a single scan.  Should the file hold the same address more than
+    /// once, the account with the largest IndexOffset wins.
+    pub fn to_account_map(&self) -> TieredStorageResult<HashMap<Pubkey, StoredAccountMeta<'_>>> {
+        Ok(self
+            .accounts(IndexOffset(0))?
+            .into_iter()
+            .map(|account| (*account.pubkey(), account))
+            .collect())
+    }
+
+    /// Returns up to `count` accounts starting from `start`, stopping early
+    /// when the end of the file is reached.  An empty vector is returned if
+    /// `start` is at or past the end.
+    pub fn get_account_range(
+        &self,
+        start: IndexOffset,
+        count: usize,
+    ) -> TieredStorageResult<Vec<StoredAccountMeta>> {
+        match self {
+            Self::Hot(hot) => hot.get_account_range(start, count),
+        }
+    }
 }
@@ -0,0 +1,281 @@
+//! Recovery of hot accounts files whose footer has been lost, e.g. when the
+//! node crashed while write_accounts() was persisting it.
+//!
+//! Hot account metas do not record the size of their account blocks, so the
+//! account blocks cannot be walked without the index block.  Instead, the
+//! index block is located by its account offsets, which start at 0 and are
+//! strictly increasing, and each candidate layout is checked against the
+//! account metas it implies and against whatever remains of the footer.
+
+use {
+    crate::tiered_storage::{
+        error::TieredStorageError,
+        file::TieredStorageFile,
+        footer::{TieredStorageFooter, TieredStorageMagicNumber, FOOTER_SIZE, FOOTER_TAIL_SIZE},
+        hot::{
+            new_hot_footer, HotAccountMeta, HotAccountOffset, HOT_ACCOUNT_ALIGNMENT,
+            HOT_BLOCK_ALIGNMENT,
+        },
+        index::{IndexBlockFormat, IndexOffset},
+        meta::{AccountMetaOptionalFields, TieredAccountMeta},
+        mmap_utils::get_pod,
+        readable::TieredStorageReader,
+        TieredStorageResult,
+    },
+    memmap2::{Mmap, MmapOptions},
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{
+        fs::{self, OpenOptions},
+        mem,
+        path::{Path, PathBuf},
+    },
+};
+
+/// The size of the leading footer fields, from account_meta_format up to
+/// owners_block_offset, which describe the layout of the file.
+const FOOTER_LAYOUT_FIELDS_SIZE: usize = 48;
+
+/// The offset of the footer tail, which directly follows the slot.
+const FOOTER_TAIL_OFFSET: usize = FOOTER_SIZE - FOOTER_TAIL_SIZE;
+
+/// The offset of the slot within the footer.
+const FOOTER_SLOT_OFFSET: usize = FOOTER_TAIL_OFFSET - mem::size_of::<Slot>();
+
+/// The outcome of a successful TieredStorage::recover().
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RecoveryOutcome {
+    /// The file already had a valid footer and has been left untouched.
+    AlreadyValid,
+    /// The footer of the file has been rebuilt, and the file now holds the
+    /// specified number of accounts.  The original file has been kept with
+    /// the ".corrupt" suffix.
+    Recovered { num_accounts: usize },
+}
+
+/// Rebuilds the footer of the hot accounts file at `path` if it is missing
+/// or torn.  See TieredStorage::recover() for details.
+pub(crate) fn recover(path: &Path) -> TieredStorageResult<RecoveryOutcome> {
+    if TieredStorageReader::new_from_path(path).is_ok() {
+        return Ok(RecoveryOutcome::AlreadyValid);
+    }
+
+    let file = OpenOptions::new().read(true).open(path)?;
+    let mmap = unsafe { MmapOptions::new().map(&file)? };
+    let Some((footer, body_size)) = find_footer(&mmap) else {
+        return Err(TieredStorageError::Unrecoverable(path.to_path_buf()));
+    };
+
+    // The recovered file is assembled and validated next to the original,
+    // which is only replaced once the recovered file reads back.  As the
+    // layout is inferred, the original is kept rather than discarded.
+    let recovering_path = with_suffix(path, ".recovering");
+    let corrupt_path = with_suffix(path, ".corrupt");
+    let result = write_recovered_file(&recovering_path, &mmap[..body_size], &footer)
+        .and_then(|_| {
+            let reader = TieredStorageReader::new_from_path(&recovering_path)?;
+            reader.validate_index()?;
+            reader.accounts(IndexOffset(0))?;
+            Ok(())
+        })
+        .and_then(|_| Ok(fs::rename(path, &corrupt_path)?))
+        .and_then(|_| {
+            fs::rename(&recovering_path, path).map_err(|err| {
+                let _ = fs::rename(&corrupt_path, path);
+                err.into()
+            })
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&recovering_path);
+    }
+    result.map(|_| RecoveryOutcome::Recovered {
+        num_accounts: footer.account_entry_count as usize,
+    })
+}
+
+/// Returns `path` with `suffix` appended to its file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Writes `body`, the account, index, and owners blocks of the recovered
+/// file, followed by `footer` into a new file at `path`.
+fn write_recovered_file(
+    path: &Path,
+    body: &[u8],
+    footer: &TieredStorageFooter,
+) -> TieredStorageResult<()> {
+    let file = TieredStorageFile::new_writable(path)?;
+    file.write_bytes(body)?;
+    footer.write_footer_block(&file)?;
+    Ok(file.sync_all()?)
+}
+
+/// Returns the footer of the most complete layout of account, index, and
+/// owners blocks found at the beginning of `mmap`, together with the size of
+/// those blocks, or None if there is no such layout.
+///
+/// Only the AddressesThenOffsets index block format can be recovered, as
+/// the account offsets of a sorted index are not in increasing order.
+///
+/// Note that the account blocks alone can also be taken for fewer accounts
+/// with larger blocks, which is how a file that lost part of its index
+/// block may be recovered, so the number of accounts found should be
+/// checked by callers that know how many to expect.
+fn find_footer(mmap: &Mmap) -> Option<(TieredStorageFooter, usize)> {
+    let file_size = mmap.len();
+    let mut found: Option<(TieredStorageFooter, usize)> = None;
+    let mut consider = |footer: TieredStorageFooter, body_size: usize| {
+        // Whatever follows the blocks must be the beginning of the footer.
+        // The layout with the most accounts, and then the one that leaves
+        // the least of the footer, is kept, as a few accounts are easily
+        // matched by chance.
+        if body_size > file_size
+            || file_size - body_size >= FOOTER_SIZE
+            || found.as_ref().is_some_and(|(found_footer, found_size)| {
+                (found_footer.account_entry_count, *found_size)
+                    >= (footer.account_entry_count, body_size)
+            })
+        {
+            return;
+        }
+        if let Some(footer) = with_footer_remnant(footer, &mmap[body_size..]) {
+            found = Some((footer, body_size));
+        }
+    };
+
+    // a file whose accounts were never written
+    consider(new_hot_footer(), 0);
+
+    let index_entry_size = IndexBlockFormat::AddressesThenOffsets.entry_size::<HotAccountOffset>();
+    let first_offsets_start = mem::size_of::<HotAccountMeta>() + mem::size_of::<Pubkey>();
+    for offsets_start in (first_offsets_start..file_size).step_by(HOT_BLOCK_ALIGNMENT) {
+        // Grow the index block one entry at a time, which moves its start
+        // back by one address, until the offsets no longer fit.
+        let mut offsets: Vec<usize> = vec![];
+        let mut max_owner_offset = 0;
+        loop {
+            let num_accounts = offsets.len() + 1;
+            let Some(index_block_offset) =
+                offsets_start.checked_sub(num_accounts * mem::size_of::<Pubkey>())
+            else {
+                break;
+            };
+            let Ok((&offset, _)) = get_pod::<u32>(
+                mmap,
+                offsets_start + offsets.len() * mem::size_of::<HotAccountOffset>(),
+            ) else {
+                break;
+            };
+            let offset = offset as usize * HOT_ACCOUNT_ALIGNMENT;
+            if offsets.last().map_or(offset != 0, |prev| offset <= *prev)
+                || offset + mem::size_of::<HotAccountMeta>() > index_block_offset
+            {
+                break;
+            }
+
+            // the previous account block ends where this one starts
+            if let Some(&prev) = offsets.last() {
+                if !block_fits(mmap, prev, offset - prev) {
+                    break;
+                }
+            }
+            max_owner_offset = max_owner_offset.max(read_meta(mmap, offset).owner_offset().0);
+            offsets.push(offset);
+
+            if !block_fits(mmap, offset, index_block_offset - offset) {
+                continue;
+            }
+            let owners_block_offset = (index_block_offset + num_accounts * index_entry_size)
+                .next_multiple_of(HOT_BLOCK_ALIGNMENT);
+            let owner_count = max_owner_offset + 1;
+            let footer = TieredStorageFooter {
+                account_entry_count: num_accounts as u32,
+                owner_count,
+                index_block_offset: index_block_offset as u64,
+                owners_block_offset: owners_block_offset as u64,
+                ..new_hot_footer()
+            };
+            consider(
+                footer,
+                owners_block_offset + owner_count as usize * mem::size_of::<Pubkey>(),
+            );
+        }
+    }
+    found
+}
+
+/// Returns the account meta at the specified byte offset, which must leave
+/// room for the meta within `mmap`.
+fn read_meta(mmap: &Mmap, offset: usize) -> &HotAccountMeta {
+    let (meta, _) = get_pod::<HotAccountMeta>(mmap, offset).unwrap();
+    meta
+}
+
+/// Returns true if the account block of `size` bytes at `offset` can hold
+/// its account meta, as well as the padding and the optional fields
+/// described by the meta, and its padding bytes are zero.
+fn block_fits(mmap: &Mmap, offset: usize, size: usize) -> bool {
+    let meta = read_meta(mmap, offset);
+    let padding = meta.account_data_padding() as usize;
+    let optional_fields_size = AccountMetaOptionalFields::size_from_flags(meta.flags());
+    if mem::size_of::<HotAccountMeta>() + padding + optional_fields_size > size {
+        return false;
+    }
+    let padding_end = offset + size - optional_fields_size;
+    mmap[padding_end - padding..padding_end]
+        .iter()
+        .all(|byte| *byte == 0)
+}
+
+/// Returns `footer` with the slot taken from `remnant`, the beginning of the
+/// lost footer, if it survived there, or None if `remnant` contradicts the
+/// layout described by `footer`.
+fn with_footer_remnant(
+    mut footer: TieredStorageFooter,
+    remnant: &[u8],
+) -> Option<TieredStorageFooter> {
+    // SAFETY: TieredStorageFooter has no padding bytes, so all its bytes are
+    // initialized.
+    let footer_bytes = unsafe {
+        std::slice::from_raw_parts(
+            (&footer as *const TieredStorageFooter).cast::<u8>(),
+            mem::size_of::<TieredStorageFooter>(),
+        )
+    };
+    let mut expected = footer_bytes.to_vec();
+    expected.extend_from_slice(bytemuck::bytes_of(&TieredStorageMagicNumber::default()));
+
+    // The addresses and the hash in between cannot be derived from the
+    // file, and are not checked.
+    let layout_size = remnant.len().min(FOOTER_LAYOUT_FIELDS_SIZE);
+    if remnant[..layout_size] != expected[..layout_size]
+        || (remnant.len() > FOOTER_TAIL_OFFSET
+            && remnant[FOOTER_TAIL_OFFSET..] != expected[FOOTER_TAIL_OFFSET..remnant.len()])
+    {
+        return None;
+    }
+    if let Some(slot) = remnant.get(FOOTER_SLOT_OFFSET..FOOTER_TAIL_OFFSET) {
+        footer.slot = Slot::from_ne_bytes(slot.try_into().unwrap());
+    }
+    Some(footer)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, memoffset::offset_of};
+
+    #[test]
+    fn test_footer_field_offsets() {
+        assert_eq!(
+            offset_of!(TieredStorageFooter, min_account_address),
+            FOOTER_LAYOUT_FIELDS_SIZE
+        );
+        assert_eq!(offset_of!(TieredStorageFooter, slot), FOOTER_SLOT_OFFSET);
+        assert_eq!(
+            offset_of!(TieredStorageFooter, format_version),
+            FOOTER_TAIL_OFFSET
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Helpers shared by the tests of the tiered storage modules.
+use {
+    super::hot::HotStorageWriter,
+    crate::{
+        account_storage::meta::{
+            StorableAccountsWithHashesAndWriteVersions, StoredAccountInfo, StoredMeta,
+        },
+        accounts_hash::AccountHash,
+    },
+    solana_sdk::{account::AccountSharedData, clock::Slot, hash::Hash, pubkey::Pubkey},
+};
+
+/// The StorableAccounts built by with_storable_accounts().
+pub(crate) type TestStorableAccounts<'a> = StorableAccountsWithHashesAndWriteVersions<
+    'a,
+    'a,
+    AccountSharedData,
+    (Slot, &'a [(&'a Pubkey, &'a AccountSharedData)]),
+    AccountHash,
+>;
+
+/// Returns `num_accounts` unique account hashes.
+pub(crate) fn new_account_hashes(num_accounts: usize) -> Vec<AccountHash> {
+    std::iter::repeat_with(|| AccountHash(Hash::new_unique()))
+        .take(num_accounts)
+        .collect()
+}
+
+/// Calls `f` with the StorableAccounts of the specified accounts for `slot`,
+/// which carry the specified account hashes and the write versions of
+/// their StoredMeta.
+pub(crate) fn with_storable_accounts<R>(
+    slot: Slot,
+    accounts: &[(StoredMeta, AccountSharedData)],
+    hashes: &[AccountHash],
+    f: impl FnOnce(&TestStorableAccounts<'_>) -> R,
+) -> R {
+    let account_refs: Vec<_> = accounts
+        .iter()
+        .map(|account| (&account.0.pubkey, &account.1))
+        .collect();
+    let account_data = (slot, &account_refs[..]);
+    let write_versions: Vec<_> = accounts
+        .iter()
+        .map(|account| account.0.write_version_obsolete)
+        .collect();
+    let storable_accounts =
+        StorableAccountsWithHashesAndWriteVersions::new_with_hashes_and_write_versions(
+            &account_data,
+            hashes.to_vec(),
+            write_versions,
+        );
+    f(&storable_accounts)
+}
+
+/// Writes the specified accounts with unique account hashes through
+/// `writer`, and returns the account hashes.
+pub(crate) fn write_hot_accounts(
+    writer: HotStorageWriter,
+    accounts: &[(StoredMeta, AccountSharedData)],
+) -> Vec<AccountHash> {
+    let hashes = new_account_hashes(accounts.len());
+    write_hot_accounts_with_hashes(writer, Slot::MAX, accounts, &hashes);
+    hashes
+}
+
+/// Writes the specified accounts with the specified account hashes for
+/// `slot` through `writer`, and returns their StoredAccountInfo.
+pub(crate) fn write_hot_accounts_with_hashes(
+    writer: HotStorageWriter,
+    slot: Slot,
+    accounts: &[(StoredMeta, AccountSharedData)],
+    hashes: &[AccountHash],
+) -> Vec<StoredAccountInfo> {
+    with_storable_accounts(slot, accounts, hashes, |storable_accounts| {
+        writer.write_accounts(storable_accounts, 0).unwrap()
+    })
+}
@@ -0,0 +1,76 @@
+use {
+    solana_accounts_db::{
+        account_storage::meta::StorableAccountsWithHashesAndWriteVersions,
+        accounts_hash::AccountHash,
+        tiered_storage::{
+            hot::HotStorageWriter, readable::TieredStorageReader, total_mapped_bytes,
+        },
+    },
+    solana_sdk::{account::AccountSharedData, clock::Slot, hash::Hash, pubkey::Pubkey},
+    std::path::Path,
+    tempfile::TempDir,
+};
+
+fn write_hot_file(path: &Path, num_accounts: usize) {
+    let accounts: Vec<_> = (0..num_accounts)
+        .map(|i| {
+            (
+                Pubkey::new_unique(),
+                AccountSharedData::new(i as u64 + 1, i * 10, &Pubkey::new_unique()),
+            )
+        })
+        .collect();
+    let account_refs: Vec<_> = accounts
+        .iter()
+        .map(|account| (&account.0, &account.1))
+        .collect();
+    let account_data = (Slot::MAX, &account_refs[..]);
+    let hashes: Vec<_> = std::iter::repeat_with(|| AccountHash(Hash::new_unique()))
+        .take(num_accounts)
+        .collect();
+    let storable_accounts =
+        StorableAccountsWithHashesAndWriteVersions::new_with_hashes_and_write_versions(
+            &account_data,
+            hashes,
+            vec![0; num_accounts],
+        );
+
+    HotStorageWriter::new(path)
+        .unwrap()
+        .write_accounts(&storable_accounts, 0)
+        .unwrap();
+}
+
+// This lives in its own test binary, as the mapped bytes are tracked
+// process-wide and would otherwise be affected by concurrent tests.
+#[test]
+fn test_total_mapped_bytes() {
+    let temp_dir = TempDir::new().unwrap();
+    let path_a = temp_dir.path().join("test_total_mapped_bytes_a");
+    let path_b = temp_dir.path().join("test_total_mapped_bytes_b");
+    write_hot_file(&path_a, 10);
+    write_hot_file(&path_b, 100);
+
+    let initial_mapped_bytes = total_mapped_bytes();
+    let reader_a = TieredStorageReader::new_from_path(&path_a).unwrap();
+    let reader_b = TieredStorageReader::new_from_path(&path_b).unwrap();
+    assert_eq!(
+        reader_a.mapped_len(),
+        std::fs::metadata(&path_a).unwrap().len()
+    );
+    assert_eq!(
+        reader_b.mapped_len(),
+        std::fs::metadata(&path_b).unwrap().len()
+    );
+    assert_eq!(
+        total_mapped_bytes(),
+        initial_mapped_bytes + reader_a.mapped_len() + reader_b.mapped_len()
+    );
+
+    let mapped_len_b = reader_b.mapped_len();
+    drop(reader_a);
+    assert_eq!(total_mapped_bytes(), initial_mapped_bytes + mapped_len_b);
+
+    drop(reader_b);
+    assert_eq!(total_mapped_bytes(), initial_mapped_bytes);
+}
@@ -0,0 +1,55 @@
+use solana_cost_model::block_cost_limits::{MAX_BLOCK_UNITS, MAX_VOTE_UNITS};
+
+/// Tracks the compute units still available in the block being built, so
+/// that transactions can be checked against and charged to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct BlockBudget {
+    /// The remaining units available to all transactions.
+    block_units: u64,
+    /// The remaining units available to simple vote transactions, which are
+    /// also charged against `block_units`.
+    vote_units: u64,
+}
+
+impl Default for BlockBudget {
+    /// A budget for an empty block under the cost-model limits.
+    fn default() -> Self {
+        Self::new(MAX_BLOCK_UNITS, MAX_VOTE_UNITS)
+    }
+}
+
+impl BlockBudget {
+    pub(crate) fn new(block_units: u64, vote_units: u64) -> Self {
+        Self {
+            block_units,
+            vote_units,
+        }
+    }
+
+    /// Returns the remaining units available to all transactions.
+    pub(crate) fn remaining_block_units(&self) -> u64 {
+        self.block_units
+    }
+
+    /// Returns the remaining units available to simple vote transactions.
+    pub(crate) fn remaining_vote_units(&self) -> u64 {
+        self.vote_units
+    }
+
+    /// Deducts `cost` from the budget and returns true if it fits, otherwise
+    /// leaves the budget unchanged and returns false. Simple votes must also
+    /// fit within the remaining vote units.
+    pub(crate) fn try_reserve(&mut self, cost: u64, is_simple_vote: bool) -> bool {
+        let Some(block_units) = self.block_units.checked_sub(cost) else {
+            return false;
+        };
+        if is_simple_vote {
+            let Some(vote_units) = self.vote_units.checked_sub(cost) else {
+                return false;
+            };
+            self.vote_units = vote_units;
+        }
+        self.block_units = block_units;
+        true
+    }
+}
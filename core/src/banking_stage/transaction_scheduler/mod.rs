@@ -1,5 +1,7 @@
 mod batch_id_generator;
 #[allow(dead_code)]
+mod block_budget;
+#[allow(dead_code)]
 mod in_flight_tracker;
 pub(crate) mod prio_graph_scheduler;
 pub(crate) mod scheduler_controller;
@@ -11,3 +13,5 @@ mod transaction_priority_id;
 mod transaction_state;
 #[allow(dead_code)]
 mod transaction_state_container;
+#[allow(dead_code)]
+mod transaction_state_queue;
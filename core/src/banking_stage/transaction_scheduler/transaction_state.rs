@@ -1,7 +1,17 @@
 use {
+    super::block_budget::BlockBudget,
     solana_cost_model::transaction_cost::TransactionCost,
     solana_runtime::compute_budget_details::ComputeBudgetDetails,
-    solana_sdk::{slot_history::Slot, transaction::SanitizedTransaction},
+    solana_sdk::{
+        pubkey::Pubkey,
+        slot_history::Slot,
+        transaction::{SanitizedTransaction, TransactionAccountLocks},
+    },
+    std::{
+        collections::HashSet,
+        time::{Duration, Instant},
+    },
+    thiserror::Error,
 };
 
 /// Simple wrapper type to tie a sanitized transaction to max age slot.
@@ -10,6 +20,91 @@ pub(crate) struct SanitizedTransactionTTL {
     pub(crate) max_age_slot: Slot,
 }
 
+impl SanitizedTransactionTTL {
+    /// Returns a field-wise copy of `self`. This is deliberately not `Clone`,
+    /// as cloning the `SanitizedTransaction` is expensive.
+    pub(crate) fn duplicate(&self) -> Self {
+        Self {
+            transaction: self.transaction.clone(),
+            max_age_slot: self.max_age_slot,
+        }
+    }
+}
+
+/// Errors returned by fallible `TransactionState` transitions.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub(crate) enum TransactionStateError {
+    #[error("transaction already pending")]
+    AlreadyPending,
+    #[error("transaction index {0} is out of bounds")]
+    IndexOutOfBounds(usize),
+    #[error("transaction {0} is not pending")]
+    NotPending(usize),
+}
+
+/// The largest total cost, in compute units, of a `CostBucket::Small` transaction.
+pub(crate) const SMALL_COST_BUCKET_MAX_UNITS: u64 = 10_000;
+/// The largest total cost, in compute units, of a `CostBucket::Medium` transaction.
+pub(crate) const MEDIUM_COST_BUCKET_MAX_UNITS: u64 = 200_000;
+
+/// A coarse classification of transactions by their cost, for metrics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum CostBucket {
+    /// A simple vote transaction, regardless of its cost.
+    Vote,
+    /// Costs at most `SMALL_COST_BUCKET_MAX_UNITS`.
+    Small,
+    /// Costs at most `MEDIUM_COST_BUCKET_MAX_UNITS`.
+    Medium,
+    /// Costs more than `MEDIUM_COST_BUCKET_MAX_UNITS`.
+    Large,
+}
+
+/// The reason a transaction is dropped by the scheduler, for metrics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum DropReason {
+    /// The transaction is older than its max age slot.
+    Expired,
+    /// The transaction does not fit into the remaining block cost.
+    CostExceeded,
+    /// The transaction is already being tracked.
+    Duplicate,
+    /// The transaction was processed and cannot be retried.
+    NotRetryable,
+}
+
+/// The metadata of a dropped transaction, retained for logging after its
+/// `TransactionState` has been consumed by `TransactionState::into_dropped`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DroppedTransaction {
+    pub(crate) reason: DropReason,
+    pub(crate) compute_unit_price: u64,
+    /// The total cost of the transaction, in compute units.
+    pub(crate) cost: u64,
+    pub(crate) is_simple_vote: bool,
+    pub(crate) forwarded: bool,
+    pub(crate) received_at: Instant,
+}
+
+/// The variant of a `TransactionState`, without its fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum TransactionStateKind {
+    Unprocessed,
+    Pending,
+}
+
+/// An owned snapshot of the fields of a `TransactionState` reported in
+/// telemetry, cheap enough to take while holding a lock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TransactionStateSummary {
+    pub(crate) kind: TransactionStateKind,
+    pub(crate) compute_unit_price: u64,
+    pub(crate) compute_unit_limit: u64,
+    /// The total cost of the transaction, in compute units.
+    pub(crate) cost: u64,
+    pub(crate) forwarded: bool,
+}
+
 /// TransactionState is used to track the state of a transaction in the transaction scheduler
 /// and banking stage as a whole.
 ///
@@ -23,7 +118,7 @@ pub(crate) struct SanitizedTransactionTTL {
 /// When a transaction finishes processing it may be retryable. If it is retryable,
 ///   the transaction is transitioned back to the `Unprocessed` state using the
 ///   `transition_to_unprocessed` method. If it is not retryable, the state should
-///   be dropped.
+///   be dropped, using the `into_dropped` method to retain its metadata.
 ///
 /// For performance, when a transaction is transitioned to the `Pending` state, the
 ///   internal `SanitizedTransaction` is moved out of the `TransactionState` and sent
@@ -37,30 +132,70 @@ pub(crate) enum TransactionState {
         compute_budget_details: ComputeBudgetDetails,
         transaction_cost: TransactionCost,
         forwarded: bool,
+        received_at: Instant,
     },
     /// The transaction is currently scheduled or being processed.
     Pending {
         compute_budget_details: ComputeBudgetDetails,
         transaction_cost: TransactionCost,
         forwarded: bool,
+        received_at: Instant,
     },
+    /// Placeholder left by `take()` while transitioning between the states
+    /// above, which is immediately overwritten. Never observed otherwise.
+    Taken,
 }
 
 impl TransactionState {
-    /// Creates a new `TransactionState` in the `Unprocessed` state.
+    /// The factor by which the compute unit price of an already forwarded
+    /// transaction is divided when computing its `effective_priority`.
+    pub(crate) const FORWARDED_PRIORITY_PENALTY: u64 = 2;
+
+    /// The age, about ten slots, after which the `decayed_priority` of a
+    /// transaction has halved.
+    pub(crate) const PRIORITY_DECAY_HALF_LIFE: Duration = Duration::from_secs(4);
+
+    /// Creates a new `TransactionState` in the `Unprocessed` state, received now.
     pub(crate) fn new(
         transaction_ttl: SanitizedTransactionTTL,
         compute_budget_details: ComputeBudgetDetails,
         transaction_cost: TransactionCost,
+    ) -> Self {
+        Self::new_with_received_at(
+            transaction_ttl,
+            compute_budget_details,
+            transaction_cost,
+            Instant::now(),
+        )
+    }
+
+    /// Creates a new `TransactionState` in the `Unprocessed` state, received at
+    /// the specified `received_at` instant.
+    pub(crate) fn new_with_received_at(
+        transaction_ttl: SanitizedTransactionTTL,
+        compute_budget_details: ComputeBudgetDetails,
+        transaction_cost: TransactionCost,
+        received_at: Instant,
     ) -> Self {
         Self::Unprocessed {
             transaction_ttl,
             compute_budget_details,
             transaction_cost,
             forwarded: false,
+            received_at,
         }
     }
 
+    /// Returns true if the transaction is available for scheduling.
+    pub(crate) fn is_unprocessed(&self) -> bool {
+        matches!(self, Self::Unprocessed { .. })
+    }
+
+    /// Returns true if the transaction is currently scheduled or being processed.
+    pub(crate) fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending { .. })
+    }
+
     /// Returns a reference to the compute budget details of the transaction.
     pub(crate) fn compute_budget_details(&self) -> &ComputeBudgetDetails {
         match self {
@@ -72,6 +207,7 @@ impl TransactionState {
                 compute_budget_details,
                 ..
             } => compute_budget_details,
+            Self::Taken => Self::taken(),
         }
     }
 
@@ -84,19 +220,90 @@ impl TransactionState {
             Self::Pending {
                 transaction_cost, ..
             } => transaction_cost,
+            Self::Taken => Self::taken(),
+        }
+    }
+
+    /// Returns true if the transaction is a simple vote transaction.
+    pub(crate) fn is_simple_vote(&self) -> bool {
+        self.transaction_cost().is_simple_vote()
+    }
+
+    /// Returns the `CostBucket` of the transaction, based on its total cost.
+    pub(crate) fn cost_bucket(&self) -> CostBucket {
+        if self.is_simple_vote() {
+            return CostBucket::Vote;
+        }
+        match self.transaction_cost().sum() {
+            cost if cost <= SMALL_COST_BUCKET_MAX_UNITS => CostBucket::Small,
+            cost if cost <= MEDIUM_COST_BUCKET_MAX_UNITS => CostBucket::Medium,
+            _ => CostBucket::Large,
         }
     }
 
+    /// Charges the cost of the transaction to `remaining` and returns true if
+    /// it fits, otherwise leaves `remaining` unchanged and returns false.
+    pub(crate) fn try_reserve_cost(&self, remaining: &mut BlockBudget) -> bool {
+        remaining.try_reserve(self.transaction_cost().sum(), self.is_simple_vote())
+    }
+
     /// Returns the compute unit price of the transaction.
     pub(crate) fn compute_unit_price(&self) -> u64 {
         self.compute_budget_details().compute_unit_price
     }
 
+    /// Returns the priority of the transaction for local processing, derived
+    /// from its compute unit price. Already forwarded transactions are less
+    /// valuable to process locally, so their price is divided by
+    /// `FORWARDED_PRIORITY_PENALTY`, and they rank below any non-forwarded
+    /// transaction with the same resulting price.
+    pub(crate) fn effective_priority(&self) -> u64 {
+        let compute_unit_price = self.compute_unit_price();
+        let (price, tier) = if self.forwarded() {
+            (compute_unit_price / Self::FORWARDED_PRIORITY_PENALTY, 0)
+        } else {
+            (compute_unit_price, 1)
+        };
+        price.saturating_mul(2).saturating_add(tier)
+    }
+
+    /// Returns the `effective_priority` of the transaction, halved for every
+    /// `PRIORITY_DECAY_HALF_LIFE` of its age relative to `now`, so that a
+    /// transaction that keeps failing does not outrank newer ones forever.
+    ///
+    /// Between two whole half-lives the priority decays linearly, which
+    /// keeps the computation in integers. At zero age it is exactly the
+    /// `effective_priority`.
+    pub(crate) fn decayed_priority(&self, now: Instant) -> u64 {
+        let priority = self.effective_priority();
+        let half_life = Self::PRIORITY_DECAY_HALF_LIFE.as_nanos();
+        let age = self.age(now).as_nanos();
+        let Ok(halvings) = u32::try_from(age / half_life) else {
+            return 0;
+        };
+        let start = priority.checked_shr(halvings).unwrap_or(0);
+        let end = start / 2;
+        let decay = u128::from(start - end) * (age % half_life) / half_life;
+        // decay < start - end, so it fits into a u64
+        start - decay as u64
+    }
+
     /// Returns whether or not the transaction has already been forwarded.
     pub(crate) fn forwarded(&self) -> bool {
         match self {
             Self::Unprocessed { forwarded, .. } => *forwarded,
             Self::Pending { forwarded, .. } => *forwarded,
+            Self::Taken => Self::taken(),
+        }
+    }
+
+    /// Returns how long ago, relative to `now`, the transaction was received.
+    /// Unlike `max_age_slot`, this keeps advancing even if slots stall.
+    pub(crate) fn age(&self, now: Instant) -> Duration {
+        match self {
+            Self::Unprocessed { received_at, .. } => now.saturating_duration_since(*received_at),
+            Self::Pending { received_at, .. } => now.saturating_duration_since(*received_at),
+            Self::Taken => Self::taken(),
         }
     }
 
@@ -105,6 +312,63 @@ impl TransactionState {
         match self {
             Self::Unprocessed { forwarded, .. } => *forwarded = true,
             Self::Pending { forwarded, .. } => *forwarded = true,
+            Self::Taken => Self::taken(),
+        }
+    }
+
+    /// Returns a `TransactionStateSummary` of the transaction.
+    pub(crate) fn summary(&self) -> TransactionStateSummary {
+        let kind = match self {
+            Self::Unprocessed { .. } => TransactionStateKind::Unprocessed,
+            Self::Pending { .. } => TransactionStateKind::Pending,
+            Self::Taken => Self::taken(),
+        };
+        let compute_budget_details = self.compute_budget_details();
+        TransactionStateSummary {
+            kind,
+            compute_unit_price: compute_budget_details.compute_unit_price,
+            compute_unit_limit: compute_budget_details.compute_unit_limit,
+            cost: self.transaction_cost().sum(),
+            forwarded: self.forwarded(),
+        }
+    }
+
+    /// Consumes the transaction, in either state, and returns the metadata
+    /// worth logging about it together with the `reason` it was dropped.
+    pub(crate) fn into_dropped(self, reason: DropReason) -> DroppedTransaction {
+        let (compute_budget_details, transaction_cost, forwarded, received_at) = match self {
+            Self::Unprocessed {
+                compute_budget_details,
+                transaction_cost,
+                forwarded,
+                received_at,
+                ..
+            } => (
+                compute_budget_details,
+                transaction_cost,
+                forwarded,
+                received_at,
+            ),
+            Self::Pending {
+                compute_budget_details,
+                transaction_cost,
+                forwarded,
+                received_at,
+            } => (
+                compute_budget_details,
+                transaction_cost,
+                forwarded,
+                received_at,
+            ),
+            Self::Taken => Self::taken(),
+        };
+        DroppedTransaction {
+            reason,
+            compute_unit_price: compute_budget_details.compute_unit_price,
+            cost: transaction_cost.sum(),
+            is_simple_vote: transaction_cost.is_simple_vote(),
+            forwarded,
+            received_at,
         }
     }
 
@@ -122,20 +386,39 @@ impl TransactionState {
                 compute_budget_details,
                 transaction_cost,
                 forwarded,
+                received_at,
             } => {
                 *self = TransactionState::Pending {
                     compute_budget_details,
                     transaction_cost,
                     forwarded,
+                    received_at,
                 };
+                #[cfg(debug_assertions)]
+                self.assert_invariants();
                 transaction_ttl
             }
             TransactionState::Pending { .. } => {
                 panic!("transaction already pending");
             }
+            TransactionState::Taken => Self::taken(),
         }
     }
 
+    /// Fallible version of `transition_to_pending`, for call sites that cannot
+    /// prove the transaction is still `Unprocessed`.
+    ///
+    /// Returns `TransactionStateError::AlreadyPending`, leaving the state
+    /// untouched, if the transaction is already in the `Pending` state.
+    pub(crate) fn try_transition_to_pending(
+        &mut self,
+    ) -> Result<SanitizedTransactionTTL, TransactionStateError> {
+        if self.is_pending() {
+            return Err(TransactionStateError::AlreadyPending);
+        }
+        Ok(self.transition_to_pending())
+    }
+
     /// Intended to be called when a transaction is retried. This method will
     /// transition the transaction from `Pending` to `Unprocessed`.
     ///
@@ -143,20 +426,50 @@ impl TransactionState {
     /// This method will panic if the transaction is already in the `Unprocessed`
     ///   state, as this is an invalid state transition.
     pub(crate) fn transition_to_unprocessed(&mut self, transaction_ttl: SanitizedTransactionTTL) {
+        self.transition_to_unprocessed_inner(transaction_ttl, None);
+    }
+
+    /// Same as `transition_to_unprocessed`, but also replaces the cost of the
+    /// transaction with `transaction_cost`, e.g. an estimate learned from the
+    /// failed processing attempt.
+    ///
+    /// # Panics
+    /// This method will panic if the transaction is already in the `Unprocessed`
+    ///   state, as this is an invalid state transition.
+    pub(crate) fn transition_to_unprocessed_with_cost(
+        &mut self,
+        transaction_ttl: SanitizedTransactionTTL,
+        transaction_cost: TransactionCost,
+    ) {
+        self.transition_to_unprocessed_inner(transaction_ttl, Some(transaction_cost));
+    }
+
+    /// Transitions the transaction from `Pending` to `Unprocessed`, keeping its
+    /// cost unless a replacement `transaction_cost` is given.
+    fn transition_to_unprocessed_inner(
+        &mut self,
+        transaction_ttl: SanitizedTransactionTTL,
+        transaction_cost: Option<TransactionCost>,
+    ) {
         match self.take() {
             TransactionState::Unprocessed { .. } => panic!("already unprocessed"),
             TransactionState::Pending {
                 compute_budget_details,
-                transaction_cost,
+                transaction_cost: pending_transaction_cost,
                 forwarded,
+                received_at,
             } => {
                 *self = Self::Unprocessed {
                     transaction_ttl,
                     compute_budget_details,
-                    transaction_cost,
+                    transaction_cost: transaction_cost.unwrap_or(pending_transaction_cost),
                     forwarded,
-                }
+                    received_at,
+                };
+                #[cfg(debug_assertions)]
+                self.assert_invariants();
             }
+            TransactionState::Taken => Self::taken(),
         }
     }
 
@@ -170,40 +483,261 @@ impl TransactionState {
                 transaction_ttl, ..
             } => transaction_ttl,
             Self::Pending { .. } => panic!("transaction is pending"),
+            Self::Taken => Self::taken(),
+        }
+    }
+
+    /// Updates the `max_age_slot` of the transaction, e.g. after it has been
+    /// re-sanitized against a newer bank.
+    ///
+    /// # Panics
+    /// This method will panic if the transaction is in the `Pending` state.
+    pub(crate) fn update_max_age_slot(&mut self, max_age_slot: Slot) {
+        match self {
+            Self::Unprocessed {
+                transaction_ttl, ..
+            } => transaction_ttl.max_age_slot = max_age_slot,
+            Self::Pending { .. } => panic!("transaction is pending"),
+            Self::Taken => Self::taken(),
+        }
+    }
+
+    /// Replaces the cost of the transaction with the one returned by
+    /// `compute_cost` for the held transaction, e.g. after cost model
+    /// parameters changed with a feature activation.
+    ///
+    /// # Panics
+    /// This method will panic if the transaction is in the `Pending` state.
+    pub(crate) fn recompute_cost(
+        &mut self,
+        compute_cost: impl FnOnce(&SanitizedTransaction) -> TransactionCost,
+    ) {
+        match self {
+            Self::Unprocessed {
+                transaction_ttl,
+                transaction_cost,
+                ..
+            } => *transaction_cost = compute_cost(&transaction_ttl.transaction),
+            Self::Pending { .. } => panic!("transaction is pending"),
+            Self::Taken => Self::taken(),
+        }
+    }
+
+    /// Returns true if the transaction can no longer be processed in
+    /// `current_slot`, i.e. its `max_age_slot` is behind it.
+    ///
+    /// `Pending` transactions are in flight and never reported as expired,
+    /// so this is safe to call in a reaping loop over all transactions.
+    pub(crate) fn is_expired(&self, current_slot: Slot) -> bool {
+        match self {
+            Self::Unprocessed {
+                transaction_ttl, ..
+            } => transaction_ttl.max_age_slot < current_slot,
+            Self::Pending { .. } => false,
+            Self::Taken => Self::taken(),
+        }
+    }
+
+    /// Returns the writable and readonly account locks of the transaction.
+    ///
+    /// # Panics
+    /// This method will panic if the transaction is in the `Pending` state.
+    pub(crate) fn account_locks(&self) -> TransactionAccountLocks<'_> {
+        self.transaction_ttl()
+            .transaction
+            .get_account_locks_unchecked()
+    }
+
+    /// Returns whether or not the transaction loads accounts through address
+    /// lookup tables. Only versioned (v0) messages can reference lookup tables.
+    ///
+    /// # Panics
+    /// This method will panic if the transaction is in the `Pending` state.
+    pub(crate) fn uses_address_lookup_tables(&self) -> bool {
+        !self
+            .transaction_ttl()
+            .transaction
+            .message()
+            .message_address_table_lookups()
+            .is_empty()
+    }
+
+    /// Returns the first 8 bytes of the transaction's signature, a compact
+    /// fingerprint for log lines.
+    ///
+    /// # Panics
+    /// This method will panic if the transaction is in the `Pending` state.
+    pub(crate) fn short_id(&self) -> [u8; 8] {
+        let signature = self.transaction_ttl().transaction.signature();
+        let mut short_id = [0; 8];
+        short_id.copy_from_slice(&signature.as_ref()[..8]);
+        short_id
+    }
+
+    /// Checks that the fields of the current state are consistent with each
+    /// other, to catch state-machine regressions early, e.g. a `Taken`
+    /// placeholder from `take()` that was never overwritten. Only compiled in
+    /// debug and test builds, and run after every transition in debug builds.
+    ///
+    /// # Panics
+    /// This method will panic if any invariant does not hold.
+    #[cfg(any(test, debug_assertions))]
+    pub(crate) fn assert_invariants(&self) {
+        assert!(
+            !matches!(self, Self::Taken),
+            "transaction state is the placeholder left by take()"
+        );
+        if let Self::Unprocessed {
+            transaction_ttl, ..
+        } = self
+        {
+            let transaction = &transaction_ttl.transaction;
+            assert!(
+                !transaction.signatures().is_empty(),
+                "unprocessed transaction has no signature"
+            );
+            assert_eq!(
+                transaction.signatures().len(),
+                usize::from(transaction.message().header().num_required_signatures),
+                "unprocessed transaction has mismatched signatures"
+            );
         }
     }
 
     /// Internal helper to transitioning between states.
-    /// Replaces `self` with a dummy state that will immediately be overwritten in transition.
+    /// Replaces `self` with the `Taken` placeholder that will immediately be overwritten in transition.
     fn take(&mut self) -> Self {
-        core::mem::replace(
-            self,
-            Self::Pending {
-                compute_budget_details: ComputeBudgetDetails {
-                    compute_unit_price: 0,
-                    compute_unit_limit: 0,
-                },
-                transaction_cost: TransactionCost::SimpleVote {
-                    writable_accounts: vec![],
-                },
-                forwarded: false,
-            },
-        )
+        core::mem::replace(self, Self::Taken)
     }
+
+    /// Panics on the `Taken` placeholder, which no method should ever see.
+    #[cold]
+    #[track_caller]
+    fn taken() -> ! {
+        unreachable!("transaction state is the placeholder left by take()")
+    }
+}
+
+/// Returns the union of the `(writable, readonly)` account locks of a batch of
+/// transactions, so they can be reserved together. An account write-locked by
+/// any transaction in the batch only appears in the writable set.
+///
+/// # Panics
+/// This function will panic if any transaction is in the `Pending` state.
+pub(crate) fn batch_locks(states: &[TransactionState]) -> (HashSet<Pubkey>, HashSet<Pubkey>) {
+    let mut writable = HashSet::new();
+    let mut readonly = HashSet::new();
+    for state in states {
+        let account_locks = state.account_locks();
+        writable.extend(account_locks.writable.into_iter().copied());
+        readonly.extend(account_locks.readonly.into_iter().copied());
+    }
+    readonly.retain(|account| !writable.contains(account));
+    (writable, readonly)
+}
+
+/// Returns the index of the transaction that should be evicted first when the
+/// buffer is full, i.e. the one with the lowest compute unit price. Among
+/// transactions with the same price, already forwarded ones are evicted
+/// first, and then the earliest in `states`.
+///
+/// Returns `None` if `states` is empty.
+pub(crate) fn select_lowest_priority(states: &[TransactionState]) -> Option<usize> {
+    states
+        .iter()
+        .enumerate()
+        .min_by_key(|(index, state)| (state.compute_unit_price(), !state.forwarded(), *index))
+        .map(|(index, _)| index)
+}
+
+/// Transitions every `Unprocessed` transaction in `states` to `Pending`, and
+/// returns their `SanitizedTransactionTTL`s in the order of `states`.
+/// Transactions that are already `Pending` are skipped rather than causing a
+/// panic midway through the batch.
+pub(crate) fn transition_batch_to_pending(
+    states: &mut [TransactionState],
+) -> Vec<SanitizedTransactionTTL> {
+    states
+        .iter_mut()
+        .filter(|state| state.is_unprocessed())
+        .map(TransactionState::transition_to_pending)
+        .collect()
+}
+
+/// Transitions the transactions at the given indices of `states` back from
+/// `Pending` to `Unprocessed`, each with the `SanitizedTransactionTTL` it is
+/// paired with.
+///
+/// All pairs are checked before any transaction is transitioned, so on error
+/// `states` is left untouched. Returns `TransactionStateError::IndexOutOfBounds`
+/// for an index beyond `states`, and `TransactionStateError::NotPending` for an
+/// index whose transaction is not `Pending` or that is paired more than once.
+pub(crate) fn transition_batch_to_unprocessed(
+    states: &mut [TransactionState],
+    transaction_ttls: impl IntoIterator<Item = (usize, SanitizedTransactionTTL)>,
+) -> Result<(), TransactionStateError> {
+    let transaction_ttls: Vec<_> = transaction_ttls.into_iter().collect();
+    let mut paired = vec![false; states.len()];
+    for (index, _) in &transaction_ttls {
+        let state = states
+            .get(*index)
+            .ok_or(TransactionStateError::IndexOutOfBounds(*index))?;
+        if !state.is_pending() || std::mem::replace(&mut paired[*index], true) {
+            return Err(TransactionStateError::NotPending(*index));
+        }
+    }
+    for (index, transaction_ttl) in transaction_ttls {
+        states[index].transition_to_unprocessed(transaction_ttl);
+    }
+    Ok(())
+}
+
+/// Splits `states` into `(not_forwarded, forwarded)` transactions, preserving
+/// their relative order.
+pub(crate) fn partition_by_forwarded(
+    states: Vec<TransactionState>,
+) -> (Vec<TransactionState>, Vec<TransactionState>) {
+    states.into_iter().partition(|state| !state.forwarded())
+}
+
+/// Counts the transactions in `states` by compute unit price, using the
+/// ascending `buckets` boundaries. The returned vector has one more count than
+/// there are boundaries: the `i`th count covers prices in
+/// `[buckets[i - 1], buckets[i])`, with the first and last buckets unbounded
+/// below and above respectively. A price equal to a boundary is therefore
+/// counted in the bucket that starts at it.
+pub(crate) fn price_histogram(states: &[TransactionState], buckets: &[u64]) -> Vec<usize> {
+    debug_assert!(buckets.windows(2).all(|window| window[0] <= window[1]));
+    let mut counts = vec![0; buckets.len() + 1];
+    for state in states {
+        let price = state.compute_unit_price();
+        counts[buckets.partition_point(|boundary| *boundary <= price)] += 1;
+    }
+    counts
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use {
         super::*,
         solana_cost_model::transaction_cost::UsageCostDetails,
         solana_sdk::{
-            compute_budget::ComputeBudgetInstruction, hash::Hash, message::Message,
-            signature::Keypair, signer::Signer, system_instruction, transaction::Transaction,
+            compute_budget::ComputeBudgetInstruction,
+            hash::Hash,
+            instruction::{AccountMeta, Instruction},
+            message::{
+                v0::{self, LoadedAddresses, MessageAddressTableLookup},
+                Message, MessageHeader, SimpleAddressLoader, VersionedMessage,
+            },
+            pubkey::Pubkey,
+            signature::Keypair,
+            signer::Signer,
+            system_instruction,
+            transaction::{Transaction, VersionedTransaction},
         },
     };
 
-    fn create_transaction_state(compute_unit_price: u64) -> TransactionState {
+    pub(crate) fn create_transaction_state(compute_unit_price: u64) -> TransactionState {
         let from_keypair = Keypair::new();
         let ixs = vec![
             system_instruction::transfer(
@@ -257,23 +791,279 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_try_transition_to_pending() {
+        let mut transaction_state = create_transaction_state(0);
+        let transaction_ttl = transaction_state.try_transition_to_pending().unwrap();
+        assert_eq!(transaction_ttl.max_age_slot, Slot::MAX);
+        assert!(transaction_state.is_pending());
+
+        assert_eq!(
+            transaction_state.try_transition_to_pending().err(),
+            Some(TransactionStateError::AlreadyPending)
+        );
+        assert!(transaction_state.is_pending());
+
+        // the failed transition must not have disturbed the pending state
+        transaction_state.transition_to_unprocessed(transaction_ttl);
+        assert!(transaction_state.is_unprocessed());
+    }
+
     #[test]
     #[should_panic(expected = "already unprocessed")]
     fn test_transition_to_unprocessed_panic() {
         let mut transaction_state = create_transaction_state(0);
 
-        // Manually clone `SanitizedTransactionTTL`
-        let SanitizedTransactionTTL {
-            transaction,
-            max_age_slot,
-        } = transaction_state.transaction_ttl();
-        let transaction_ttl = SanitizedTransactionTTL {
-            transaction: transaction.clone(),
-            max_age_slot: *max_age_slot,
-        };
+        let transaction_ttl = transaction_state.transaction_ttl().duplicate();
         transaction_state.transition_to_unprocessed(transaction_ttl); // invalid transition
     }
 
+    #[test]
+    fn test_transition_to_unprocessed_with_cost() {
+        let mut transaction_state = create_transaction_state(5);
+        transaction_state.set_forwarded();
+        assert_eq!(transaction_state.transaction_cost().sum(), 5000);
+
+        let transaction_ttl = transaction_state.transition_to_pending();
+        let new_cost = TransactionCost::Transaction(UsageCostDetails {
+            signature_cost: 5000,
+            bpf_execution_cost: 1000,
+            ..UsageCostDetails::default()
+        });
+        transaction_state.transition_to_unprocessed_with_cost(transaction_ttl, new_cost);
+        assert!(transaction_state.is_unprocessed());
+        assert_eq!(transaction_state.transaction_cost().sum(), 6000);
+        assert_eq!(transaction_state.compute_unit_price(), 5);
+        assert!(transaction_state.forwarded());
+
+        // the plain transition preserves the updated cost
+        let transaction_ttl = transaction_state.transition_to_pending();
+        transaction_state.transition_to_unprocessed(transaction_ttl);
+        assert_eq!(transaction_state.transaction_cost().sum(), 6000);
+    }
+
+    #[test]
+    #[should_panic(expected = "already unprocessed")]
+    fn test_transition_to_unprocessed_with_cost_panic() {
+        let mut transaction_state = create_transaction_state(0);
+
+        let transaction_ttl = transaction_state.transaction_ttl().duplicate();
+        transaction_state.transition_to_unprocessed_with_cost(
+            transaction_ttl,
+            TransactionCost::SimpleVote {
+                writable_accounts: vec![],
+            },
+        );
+    }
+
+    #[test]
+    fn test_try_reserve_cost() {
+        // each transaction created for tests costs 5000 units
+        let states: Vec<_> = (0..4).map(create_transaction_state).collect();
+        let mut budget = BlockBudget::new(12_000, 12_000);
+
+        assert!(states[0].try_reserve_cost(&mut budget));
+        assert!(states[1].try_reserve_cost(&mut budget));
+        assert_eq!(budget.remaining_block_units(), 2_000);
+
+        // the overflowing transaction is rejected without touching the budget
+        let before = budget;
+        assert!(!states[2].try_reserve_cost(&mut budget));
+        assert_eq!(budget, before);
+
+        // vote units are only charged for simple votes
+        assert_eq!(budget.remaining_vote_units(), 12_000);
+        let vote = TransactionState::new(
+            states[3].transaction_ttl().duplicate(),
+            ComputeBudgetDetails {
+                compute_unit_price: 0,
+                compute_unit_limit: 0,
+            },
+            TransactionCost::SimpleVote {
+                writable_accounts: vec![],
+            },
+        );
+        let mut budget = BlockBudget::new(u64::MAX, 0);
+        assert!(!vote.try_reserve_cost(&mut budget));
+        assert_eq!(budget, BlockBudget::new(u64::MAX, 0));
+        assert!(states[3].try_reserve_cost(&mut budget));
+    }
+
+    #[test]
+    fn test_assert_invariants() {
+        let mut transaction_state = create_transaction_state(5);
+        transaction_state.assert_invariants();
+        for _ in 0..3 {
+            let transaction_ttl = transaction_state.transition_to_pending();
+            transaction_state.assert_invariants();
+            transaction_state.transition_to_unprocessed(transaction_ttl);
+            transaction_state.assert_invariants();
+        }
+
+        let transaction_ttl = transaction_state.transition_to_pending();
+        transaction_state.assert_invariants();
+        transaction_state.transition_to_unprocessed_with_cost(
+            transaction_ttl,
+            TransactionCost::Transaction(UsageCostDetails {
+                signature_cost: 6000,
+                ..UsageCostDetails::default()
+            }),
+        );
+        transaction_state.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "placeholder")]
+    fn test_assert_invariants_dummy_state() {
+        let mut transaction_state = create_transaction_state(5);
+        let _ = transaction_state.take();
+        transaction_state.assert_invariants();
+    }
+
+    #[test]
+    fn test_assert_invariants_empty_simple_vote() {
+        // a legal cost that must not be mistaken for the placeholder
+        let mut transaction_state = create_transaction_state(5);
+        let transaction_ttl = transaction_state.transition_to_pending();
+        transaction_state.transition_to_unprocessed_with_cost(
+            transaction_ttl,
+            TransactionCost::SimpleVote {
+                writable_accounts: vec![],
+            },
+        );
+        transaction_state.assert_invariants();
+        let transaction_ttl = transaction_state.transition_to_pending();
+        transaction_state.assert_invariants();
+        transaction_state.transition_to_unprocessed(transaction_ttl);
+        transaction_state.assert_invariants();
+    }
+
+    #[test]
+    fn test_into_dropped() {
+        let received_at = Instant::now();
+        let transaction_state = create_transaction_state(42);
+        let mut transaction_state = TransactionState::new_with_received_at(
+            transaction_state.transaction_ttl().duplicate(),
+            transaction_state.compute_budget_details().clone(),
+            TransactionCost::Transaction(UsageCostDetails {
+                signature_cost: 5000,
+                bpf_execution_cost: 20_000,
+                ..UsageCostDetails::default()
+            }),
+            received_at,
+        );
+        transaction_state.set_forwarded();
+        assert_eq!(
+            transaction_state.into_dropped(DropReason::Expired),
+            DroppedTransaction {
+                reason: DropReason::Expired,
+                compute_unit_price: 42,
+                cost: 25_000,
+                is_simple_vote: false,
+                forwarded: true,
+                received_at,
+            }
+        );
+
+        // pending transactions can be dropped too
+        let mut transaction_state = create_transaction_state(7);
+        let _transaction_ttl = transaction_state.transition_to_pending();
+        let dropped = transaction_state.into_dropped(DropReason::NotRetryable);
+        assert_eq!(dropped.reason, DropReason::NotRetryable);
+        assert_eq!(dropped.compute_unit_price, 7);
+        assert_eq!(dropped.cost, 5000);
+        assert!(!dropped.forwarded);
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut transaction_state = create_transaction_state(42);
+        transaction_state.set_forwarded();
+        let assert_summary = |transaction_state: &TransactionState, kind| {
+            assert_eq!(
+                transaction_state.summary(),
+                TransactionStateSummary {
+                    kind,
+                    compute_unit_price: transaction_state.compute_unit_price(),
+                    compute_unit_limit: transaction_state
+                        .compute_budget_details()
+                        .compute_unit_limit,
+                    cost: transaction_state.transaction_cost().sum(),
+                    forwarded: transaction_state.forwarded(),
+                }
+            );
+        };
+        assert_summary(&transaction_state, TransactionStateKind::Unprocessed);
+
+        let _transaction_ttl = transaction_state.transition_to_pending();
+        assert_summary(&transaction_state, TransactionStateKind::Pending);
+        let summary = transaction_state.summary();
+        assert_eq!(summary.compute_unit_price, 42);
+        assert!(summary.forwarded);
+    }
+
+    #[test]
+    fn test_cost_bucket() {
+        // transfers created for tests cost 5000 units at any price
+        assert_eq!(create_transaction_state(0).cost_bucket(), CostBucket::Small);
+        assert_eq!(
+            create_transaction_state(1_000_000).cost_bucket(),
+            CostBucket::Small
+        );
+
+        let transaction_state = create_transaction_state(0);
+        let with_cost = |transaction_cost| {
+            TransactionState::new(
+                transaction_state.transaction_ttl().duplicate(),
+                transaction_state.compute_budget_details().clone(),
+                transaction_cost,
+            )
+        };
+        let with_bpf_cost = |bpf_execution_cost| {
+            with_cost(TransactionCost::Transaction(UsageCostDetails {
+                bpf_execution_cost,
+                ..UsageCostDetails::default()
+            }))
+        };
+        assert_eq!(
+            with_cost(TransactionCost::SimpleVote {
+                writable_accounts: vec![],
+            })
+            .cost_bucket(),
+            CostBucket::Vote
+        );
+        assert_eq!(
+            with_bpf_cost(SMALL_COST_BUCKET_MAX_UNITS).cost_bucket(),
+            CostBucket::Small
+        );
+        assert_eq!(
+            with_bpf_cost(SMALL_COST_BUCKET_MAX_UNITS + 1).cost_bucket(),
+            CostBucket::Medium
+        );
+        assert_eq!(
+            with_bpf_cost(MEDIUM_COST_BUCKET_MAX_UNITS).cost_bucket(),
+            CostBucket::Medium
+        );
+        assert_eq!(
+            with_bpf_cost(MEDIUM_COST_BUCKET_MAX_UNITS + 1).cost_bucket(),
+            CostBucket::Large
+        );
+    }
+
+    #[test]
+    fn test_duplicate_transaction_ttl() {
+        let mut transaction_state = create_transaction_state(0);
+        transaction_state.update_max_age_slot(42);
+        let transaction_ttl = transaction_state.transaction_ttl();
+
+        let duplicate = transaction_ttl.duplicate();
+        assert_eq!(duplicate.max_age_slot, 42);
+        assert_eq!(
+            duplicate.transaction.signature(),
+            transaction_ttl.transaction.signature()
+        );
+    }
+
     #[test]
     fn test_transition_to_unprocessed() {
         let mut transaction_state = create_transaction_state(0);
@@ -293,6 +1083,233 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_is_unprocessed_and_is_pending() {
+        let mut transaction_state = create_transaction_state(0);
+        assert!(transaction_state.is_unprocessed());
+        assert!(!transaction_state.is_pending());
+
+        let transaction_ttl = transaction_state.transition_to_pending();
+        assert!(!transaction_state.is_unprocessed());
+        assert!(transaction_state.is_pending());
+
+        transaction_state.transition_to_unprocessed(transaction_ttl);
+        assert!(transaction_state.is_unprocessed());
+        assert!(!transaction_state.is_pending());
+    }
+
+    #[test]
+    fn test_effective_priority() {
+        for compute_unit_price in [0, 1, 15, 1_000] {
+            let transaction_state = create_transaction_state(compute_unit_price);
+            let mut forwarded_state = create_transaction_state(compute_unit_price);
+            forwarded_state.set_forwarded();
+            assert!(transaction_state.effective_priority() > forwarded_state.effective_priority());
+        }
+
+        // a forwarded transaction can still outrank a much cheaper one
+        let cheap_state = create_transaction_state(10);
+        let mut forwarded_state = create_transaction_state(
+            10 * TransactionState::FORWARDED_PRIORITY_PENALTY
+                + TransactionState::FORWARDED_PRIORITY_PENALTY,
+        );
+        forwarded_state.set_forwarded();
+        assert!(forwarded_state.effective_priority() > cheap_state.effective_priority());
+
+        // ordering by price is preserved among non-forwarded transactions
+        assert!(
+            create_transaction_state(2).effective_priority()
+                > create_transaction_state(1).effective_priority()
+        );
+    }
+
+    #[test]
+    fn test_decayed_priority() {
+        let transaction_state = create_transaction_state(1_000);
+        let TransactionState::Unprocessed {
+            transaction_ttl,
+            compute_budget_details,
+            transaction_cost,
+            ..
+        } = transaction_state
+        else {
+            unreachable!();
+        };
+        let received_at = Instant::now();
+        let transaction_state = TransactionState::new_with_received_at(
+            transaction_ttl,
+            compute_budget_details,
+            transaction_cost,
+            received_at,
+        );
+        let priority = transaction_state.effective_priority();
+        let half_life = TransactionState::PRIORITY_DECAY_HALF_LIFE;
+
+        assert_eq!(transaction_state.decayed_priority(received_at), priority);
+        assert_eq!(
+            transaction_state.decayed_priority(received_at + half_life),
+            priority / 2
+        );
+        assert_eq!(
+            transaction_state.decayed_priority(received_at + half_life * 2),
+            priority / 4
+        );
+        // halfway between two half-lives, the decay is linear
+        assert_eq!(
+            transaction_state.decayed_priority(received_at + half_life / 2),
+            priority - (priority - priority / 2) / 2
+        );
+
+        let mut prev_priority = priority;
+        for age_secs in 1..100 {
+            let decayed_priority =
+                transaction_state.decayed_priority(received_at + Duration::from_secs(age_secs));
+            assert!(decayed_priority <= prev_priority);
+            prev_priority = decayed_priority;
+        }
+        assert!(prev_priority < priority);
+        assert_eq!(
+            transaction_state.decayed_priority(received_at + Duration::from_secs(1_000_000)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_update_max_age_slot() {
+        let mut transaction_state = create_transaction_state(0);
+        assert_eq!(transaction_state.transaction_ttl().max_age_slot, Slot::MAX);
+
+        transaction_state.update_max_age_slot(42);
+        assert_eq!(transaction_state.transaction_ttl().max_age_slot, 42);
+
+        // the updated slot survives state transitions
+        let transaction_ttl = transaction_state.transition_to_pending();
+        assert_eq!(transaction_ttl.max_age_slot, 42);
+        transaction_state.transition_to_unprocessed(transaction_ttl);
+        assert_eq!(transaction_state.transaction_ttl().max_age_slot, 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "transaction is pending")]
+    fn test_update_max_age_slot_panic() {
+        let mut transaction_state = create_transaction_state(0);
+        let _ = transaction_state.transition_to_pending();
+        transaction_state.update_max_age_slot(42); // pending state, the transaction ttl is not available
+    }
+
+    #[test]
+    fn test_recompute_cost() {
+        let mut transaction_state = create_transaction_state(0);
+        assert_eq!(transaction_state.transaction_cost().sum(), 5000);
+        let expected_signature = *transaction_state.transaction_ttl().transaction.signature();
+
+        transaction_state.recompute_cost(|transaction| {
+            assert_eq!(transaction.signature(), &expected_signature);
+            TransactionCost::Transaction(UsageCostDetails {
+                signature_cost: 5000,
+                write_lock_cost: 600,
+                ..UsageCostDetails::default()
+            })
+        });
+        assert!(transaction_state.is_unprocessed());
+        assert_eq!(transaction_state.transaction_cost().sum(), 5600);
+
+        // the recomputed cost is carried over the pending state
+        let transaction_ttl = transaction_state.transition_to_pending();
+        assert_eq!(transaction_state.transaction_cost().sum(), 5600);
+        transaction_state.transition_to_unprocessed(transaction_ttl);
+        assert_eq!(transaction_state.transaction_cost().sum(), 5600);
+    }
+
+    #[test]
+    #[should_panic(expected = "transaction is pending")]
+    fn test_recompute_cost_panic() {
+        let mut transaction_state = create_transaction_state(0);
+        let _ = transaction_state.transition_to_pending();
+        transaction_state.recompute_cost(|_| unreachable!());
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let mut transaction_state = create_transaction_state(0);
+        transaction_state.update_max_age_slot(42);
+        assert!(transaction_state.is_expired(43));
+        assert!(!transaction_state.is_expired(42));
+        assert!(!transaction_state.is_expired(41));
+
+        // pending transactions are in flight and never expire
+        let _ = transaction_state.transition_to_pending();
+        assert!(!transaction_state.is_expired(43));
+    }
+
+    #[test]
+    fn test_short_id() {
+        let mut transaction_state = create_transaction_state(0);
+        let signature = *transaction_state.transaction_ttl().transaction.signature();
+        let short_id = transaction_state.short_id();
+        assert_eq!(short_id, signature.as_ref()[..8]);
+
+        // ensure the short id is stable through state transitions
+        let transaction_ttl = transaction_state.transition_to_pending();
+        transaction_state.transition_to_unprocessed(transaction_ttl);
+        assert_eq!(transaction_state.short_id(), short_id);
+    }
+
+    #[test]
+    fn test_age() {
+        let transaction_state = create_transaction_state(0);
+        let TransactionState::Unprocessed {
+            transaction_ttl,
+            compute_budget_details,
+            transaction_cost,
+            ..
+        } = transaction_state
+        else {
+            unreachable!();
+        };
+
+        let now = Instant::now();
+        let received_at = now - Duration::from_secs(5);
+        let mut transaction_state = TransactionState::new_with_received_at(
+            transaction_ttl,
+            compute_budget_details,
+            transaction_cost,
+            received_at,
+        );
+        assert_eq!(transaction_state.age(now), Duration::from_secs(5));
+
+        // ensure the receipt time is not lost through state transitions
+        let transaction_ttl = transaction_state.transition_to_pending();
+        assert_eq!(transaction_state.age(now), Duration::from_secs(5));
+        transaction_state.transition_to_unprocessed(transaction_ttl);
+        assert_eq!(transaction_state.age(now), Duration::from_secs(5));
+
+        // an instant before receipt saturates to zero
+        assert_eq!(transaction_state.age(received_at), Duration::ZERO);
+        assert_eq!(
+            transaction_state.age(received_at - Duration::from_secs(1)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_is_simple_vote() {
+        let mut transaction_state = create_transaction_state(0);
+        assert!(!transaction_state.is_simple_vote());
+
+        let transaction_ttl = transaction_state.transition_to_pending();
+        let vote_state = TransactionState::new(
+            transaction_ttl,
+            transaction_state.compute_budget_details().clone(),
+            TransactionCost::SimpleVote {
+                writable_accounts: vec![],
+            },
+        );
+        assert!(vote_state.is_simple_vote());
+        // still distinguishable while pending
+        assert!(!transaction_state.is_simple_vote());
+    }
+
     #[test]
     fn test_compute_unit_price() {
         let compute_unit_price = 15;
@@ -350,4 +1367,273 @@ mod tests {
         ));
         assert_eq!(transaction_ttl.max_age_slot, Slot::MAX);
     }
+
+    #[test]
+    fn test_uses_address_lookup_tables() {
+        // legacy transactions cannot reference lookup tables
+        let transaction_state = create_transaction_state(0);
+        assert!(!transaction_state.uses_address_lookup_tables());
+
+        let keypair = Keypair::new();
+        let message = VersionedMessage::V0(v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 0,
+            },
+            recent_blockhash: Hash::default(),
+            account_keys: vec![keypair.pubkey()],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![1],
+            }],
+            instructions: vec![],
+        });
+        let tx = VersionedTransaction::try_new(message, &[&keypair]).unwrap();
+        let transaction = SanitizedTransaction::try_create(
+            tx,
+            Hash::new_unique(),
+            Some(false),
+            SimpleAddressLoader::Enabled(LoadedAddresses {
+                writable: vec![Pubkey::new_unique()],
+                readonly: vec![Pubkey::new_unique()],
+            }),
+        )
+        .unwrap();
+        let transaction_state = TransactionState::new(
+            SanitizedTransactionTTL {
+                transaction,
+                max_age_slot: Slot::MAX,
+            },
+            ComputeBudgetDetails {
+                compute_unit_price: 0,
+                compute_unit_limit: 0,
+            },
+            TransactionCost::Transaction(UsageCostDetails::default()),
+        );
+        assert!(transaction_state.uses_address_lookup_tables());
+    }
+
+    #[test]
+    fn test_batch_locks() {
+        let program_id = Pubkey::new_unique();
+        let create_state = |payer: &Keypair, writable: &[Pubkey], readonly: &[Pubkey]| {
+            let accounts = writable
+                .iter()
+                .map(|account| AccountMeta::new(*account, false))
+                .chain(
+                    readonly
+                        .iter()
+                        .map(|account| AccountMeta::new_readonly(*account, false)),
+                )
+                .collect();
+            let ix = Instruction::new_with_bytes(program_id, &[], accounts);
+            let message = Message::new(&[ix], Some(&payer.pubkey()));
+            let tx = Transaction::new(&[payer], message, Hash::default());
+            TransactionState::new(
+                SanitizedTransactionTTL {
+                    transaction: SanitizedTransaction::from_transaction_for_tests(tx),
+                    max_age_slot: Slot::MAX,
+                },
+                ComputeBudgetDetails {
+                    compute_unit_price: 0,
+                    compute_unit_limit: 0,
+                },
+                TransactionCost::Transaction(UsageCostDetails::default()),
+            )
+        };
+
+        let payers: Vec<_> = std::iter::repeat_with(Keypair::new).take(3).collect();
+        let [w1, w2, r1, r2] = std::array::from_fn(|_| Pubkey::new_unique());
+        let states = [
+            create_state(&payers[0], &[w1], &[r1]),
+            // `w2` is only read here but written by the next transaction
+            create_state(&payers[1], &[w1], &[r1, w2]),
+            create_state(&payers[2], &[w2], &[r2]),
+        ];
+
+        let (writable, readonly) = batch_locks(&states);
+        assert_eq!(
+            writable,
+            HashSet::from([
+                payers[0].pubkey(),
+                payers[1].pubkey(),
+                payers[2].pubkey(),
+                w1,
+                w2
+            ])
+        );
+        assert_eq!(readonly, HashSet::from([r1, r2, program_id]));
+
+        let (writable, readonly) = batch_locks(&[]);
+        assert!(writable.is_empty());
+        assert!(readonly.is_empty());
+    }
+
+    #[test]
+    fn test_select_lowest_priority() {
+        assert_eq!(select_lowest_priority(&[]), None);
+
+        let create_state = |compute_unit_price, forwarded| {
+            let mut transaction_state = create_transaction_state(compute_unit_price);
+            if forwarded {
+                transaction_state.set_forwarded();
+            }
+            transaction_state
+        };
+
+        // (compute unit price, forwarded)
+        let states = [
+            create_state(10, false),
+            create_state(5, false),
+            create_state(20, true),
+            create_state(5, true),
+            create_state(5, true),
+            create_state(7, false),
+        ];
+        // the cheapest forwarded transactions tie; the earliest one is chosen
+        assert_eq!(select_lowest_priority(&states), Some(3));
+        // without forwarded ones at that price, the non-forwarded one is next
+        assert_eq!(select_lowest_priority(&states[..3]), Some(1));
+        // price dominates the forwarded flag
+        assert_eq!(
+            select_lowest_priority(&[create_state(20, true), create_state(7, false)]),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_price_histogram() {
+        let buckets = [10, 100, 1_000];
+        assert_eq!(price_histogram(&[], &buckets), vec![0, 0, 0, 0]);
+
+        let states: Vec<_> = [0, 9, 10, 50, 99, 100, 1_000, 5_000]
+            .into_iter()
+            .map(create_transaction_state)
+            .collect();
+        // prices on a boundary are counted in the bucket starting at it
+        assert_eq!(price_histogram(&states, &buckets), vec![2, 3, 1, 2]);
+        // without boundaries, everything falls into a single bucket
+        assert_eq!(price_histogram(&states, &[]), vec![states.len()]);
+    }
+
+    #[test]
+    fn test_partition_by_forwarded() {
+        let (not_forwarded, forwarded) = partition_by_forwarded(vec![]);
+        assert!(not_forwarded.is_empty());
+        assert!(forwarded.is_empty());
+
+        // the compute unit price identifies each transaction
+        let states: Vec<_> = (0..6)
+            .map(|compute_unit_price| {
+                let mut transaction_state = create_transaction_state(compute_unit_price);
+                if compute_unit_price % 3 == 0 {
+                    transaction_state.set_forwarded();
+                }
+                transaction_state
+            })
+            .collect();
+
+        let (not_forwarded, forwarded) = partition_by_forwarded(states);
+        assert_eq!(not_forwarded.len(), 4);
+        assert_eq!(forwarded.len(), 2);
+        assert!(not_forwarded.iter().all(|state| !state.forwarded()));
+        assert!(forwarded.iter().all(TransactionState::forwarded));
+        assert_eq!(
+            not_forwarded
+                .iter()
+                .map(TransactionState::compute_unit_price)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 4, 5]
+        );
+        assert_eq!(
+            forwarded
+                .iter()
+                .map(TransactionState::compute_unit_price)
+                .collect::<Vec<_>>(),
+            vec![0, 3]
+        );
+    }
+
+    #[test]
+    fn test_transition_batch() {
+        let mut states: Vec<_> = (0..5).map(create_transaction_state).collect();
+        let signatures: Vec<_> = states
+            .iter()
+            .map(|state| *state.transaction_ttl().transaction.signature())
+            .collect();
+        // an already pending transaction is skipped
+        let pending_ttl = states[2].transition_to_pending();
+
+        let transaction_ttls = transition_batch_to_pending(&mut states);
+        assert_eq!(transaction_ttls.len(), 4);
+        assert!(states.iter().all(TransactionState::is_pending));
+
+        // TTLs are returned in the order of the transactions
+        let mut all_ttls = transaction_ttls;
+        all_ttls.insert(2, pending_ttl);
+        assert!(all_ttls
+            .iter()
+            .map(|transaction_ttl| transaction_ttl.transaction.signature())
+            .eq(signatures.iter()));
+
+        assert_eq!(
+            transition_batch_to_unprocessed(&mut states, all_ttls.into_iter().enumerate()),
+            Ok(())
+        );
+        assert!(states.iter().all(TransactionState::is_unprocessed));
+        // each transaction gets its own TTL back
+        for (state, signature) in states.iter().zip(&signatures) {
+            assert_eq!(state.transaction_ttl().transaction.signature(), signature);
+        }
+
+        // nothing is left to transition
+        assert!(transition_batch_to_pending(&mut []).is_empty());
+        assert_eq!(transition_batch_to_unprocessed(&mut states, vec![]), Ok(()));
+    }
+
+    #[test]
+    fn test_transition_batch_to_unprocessed_mismatch() {
+        let mut states: Vec<_> = (0..3).map(create_transaction_state).collect();
+        let transaction_ttls = transition_batch_to_pending(&mut states[..2]);
+        let duplicate_ttls = || {
+            transaction_ttls
+                .iter()
+                .map(SanitizedTransactionTTL::duplicate)
+                .enumerate()
+        };
+
+        let test_cases = [
+            (
+                duplicate_ttls()
+                    .chain([(3, transaction_ttls[0].duplicate())])
+                    .collect::<Vec<_>>(),
+                TransactionStateError::IndexOutOfBounds(3),
+            ),
+            // the transaction at index 2 was never pending
+            (
+                duplicate_ttls()
+                    .map(|(index, ttl)| (index + 1, ttl))
+                    .collect(),
+                TransactionStateError::NotPending(2),
+            ),
+            // the transaction at index 0 is paired twice
+            (
+                duplicate_ttls()
+                    .chain([(0, transaction_ttls[0].duplicate())])
+                    .collect(),
+                TransactionStateError::NotPending(0),
+            ),
+        ];
+        for (pairs, expected_error) in test_cases {
+            assert_eq!(
+                transition_batch_to_unprocessed(&mut states, pairs),
+                Err(expected_error)
+            );
+            // no transaction is transitioned on error
+            assert!(states[..2].iter().all(TransactionState::is_pending));
+            assert!(states[2].is_unprocessed());
+        }
+    }
 }
@@ -0,0 +1,246 @@
+use {
+    super::transaction_state::TransactionState, solana_sdk::slot_history::Slot,
+    std::collections::VecDeque,
+};
+
+/// A FIFO queue of `TransactionState`s assigned to a single worker thread.
+/// Each transaction is tracked along with the slot it was received in.
+#[derive(Default)]
+pub(crate) struct TransactionStateQueue {
+    states: VecDeque<(Slot, TransactionState)>,
+}
+
+impl TransactionStateQueue {
+    /// Pushes a transaction, received in `received_slot`, to the back of the queue.
+    pub(crate) fn push(&mut self, transaction_state: TransactionState, received_slot: Slot) {
+        self.states.push_back((received_slot, transaction_state));
+    }
+
+    /// Returns the number of transactions in the queue.
+    pub(crate) fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns true if the queue is empty.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Returns an iterator over the transactions in the queue, front to back.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &TransactionState> {
+        self.states.iter().map(|(_, state)| state)
+    }
+
+    /// Returns the total compute units of all transactions in the queue.
+    pub(crate) fn total_compute_units(&self) -> u64 {
+        self.iter()
+            .map(|state| state.transaction_cost().sum())
+            .sum()
+    }
+
+    /// Returns the median age, in slots relative to `current_slot`, of the
+    /// transactions in the queue, with each transaction weighted by its
+    /// compute unit price. A high value means high-fee transactions are
+    /// waiting on this queue.
+    ///
+    /// Returns 0 if the queue holds no transaction with a non-zero price.
+    pub(crate) fn fee_weighted_median_age(&self, current_slot: Slot) -> u64 {
+        let mut weighted_ages: Vec<_> = self
+            .states
+            .iter()
+            .map(|(received_slot, state)| {
+                (
+                    current_slot.saturating_sub(*received_slot),
+                    state.compute_unit_price(),
+                )
+            })
+            .collect();
+        weighted_ages.sort_unstable_by_key(|(age, _)| *age);
+
+        let total_weight: u128 = weighted_ages
+            .iter()
+            .map(|(_, weight)| u128::from(*weight))
+            .sum();
+        let mut cumulative_weight = 0u128;
+        for (age, weight) in weighted_ages {
+            cumulative_weight += u128::from(weight);
+            if cumulative_weight > 0 && 2 * cumulative_weight >= total_weight {
+                return age;
+            }
+        }
+        0
+    }
+
+    /// Removes the most recently queued `Unprocessed` transaction whose cost
+    /// is non-zero and strictly below `max_cost`.
+    fn pop_unprocessed_below(&mut self, max_cost: u64) -> Option<(Slot, TransactionState)> {
+        let index = self.states.iter().rposition(|(_, state)| {
+            let cost = state.transaction_cost().sum();
+            state.is_unprocessed() && cost > 0 && cost < max_cost
+        })?;
+        self.states.remove(index)
+    }
+}
+
+/// Moves `Unprocessed` transactions to the least-loaded queue, measured by
+/// total compute units, until no single move would reduce the imbalance
+/// further. Donors are tried from the most-loaded down, so a heavy queue
+/// holding only `Pending` transactions, which are never moved, does not stop
+/// the others from being rebalanced.
+pub(crate) fn rebalance(queues: &mut [TransactionStateQueue]) {
+    if queues.len() < 2 {
+        return;
+    }
+
+    let mut loads: Vec<_> = queues
+        .iter()
+        .map(TransactionStateQueue::total_compute_units)
+        .collect();
+    let mut donors: Vec<_> = (0..queues.len()).collect();
+    loop {
+        let (min_index, min_load) = loads
+            .iter()
+            .copied()
+            .enumerate()
+            .min_by_key(|(_, load)| *load)
+            .unwrap();
+        donors.sort_unstable_by_key(|index| std::cmp::Reverse(loads[*index]));
+
+        // Moving a transaction with cost `c` changes the gap to `|gap - 2c|`,
+        // which is only an improvement if `0 < c < gap`.
+        let Some((max_index, received_slot, transaction_state)) =
+            donors.iter().find_map(|&max_index| {
+                let gap = loads[max_index] - min_load;
+                queues[max_index].pop_unprocessed_below(gap).map(
+                    |(received_slot, transaction_state)| {
+                        (max_index, received_slot, transaction_state)
+                    },
+                )
+            })
+        else {
+            break;
+        };
+        let cost = transaction_state.transaction_cost().sum();
+        loads[max_index] -= cost;
+        loads[min_index] += cost;
+        queues[min_index].push(transaction_state, received_slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::banking_stage::transaction_scheduler::transaction_state::tests::create_transaction_state,
+    };
+
+    #[test]
+    fn test_rebalance() {
+        let mut queues = vec![
+            TransactionStateQueue::default(),
+            TransactionStateQueue::default(),
+        ];
+        for priority in 0..10 {
+            queues[0].push(create_transaction_state(priority), 0);
+        }
+        // a pending transaction must stay on the queue it was assigned to
+        let mut pending = create_transaction_state(10);
+        let _ = pending.transition_to_pending();
+        queues[0].push(pending, 0);
+
+        let per_transaction_cost = queues[0].iter().next().unwrap().transaction_cost().sum();
+        let total_cost = queues[0].total_compute_units();
+        assert_eq!(queues[1].total_compute_units(), 0);
+
+        rebalance(&mut queues);
+
+        let loads: Vec<_> = queues
+            .iter()
+            .map(TransactionStateQueue::total_compute_units)
+            .collect();
+        assert_eq!(loads.iter().sum::<u64>(), total_cost);
+        assert!(loads[0].abs_diff(loads[1]) <= per_transaction_cost);
+        assert_eq!(queues[0].len() + queues[1].len(), 11);
+        assert!(queues[1].iter().all(TransactionState::is_unprocessed));
+        assert_eq!(
+            queues[0].iter().filter(|state| state.is_pending()).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_rebalance_only_pending() {
+        let mut queues = vec![
+            TransactionStateQueue::default(),
+            TransactionStateQueue::default(),
+        ];
+        for priority in 0..4 {
+            let mut state = create_transaction_state(priority);
+            let _ = state.transition_to_pending();
+            queues[0].push(state, 0);
+        }
+
+        rebalance(&mut queues);
+        assert_eq!(queues[0].len(), 4);
+        assert!(queues[1].is_empty());
+    }
+
+    #[test]
+    fn test_rebalance_heaviest_only_pending() {
+        let mut queues = vec![
+            TransactionStateQueue::default(),
+            TransactionStateQueue::default(),
+            TransactionStateQueue::default(),
+        ];
+        for priority in 0..6 {
+            let mut state = create_transaction_state(priority);
+            let _ = state.transition_to_pending();
+            queues[0].push(state, 0);
+        }
+        for priority in 0..4 {
+            queues[1].push(create_transaction_state(priority), 0);
+        }
+        let per_transaction_cost = queues[1].iter().next().unwrap().transaction_cost().sum();
+        assert!(queues[0].total_compute_units() > queues[1].total_compute_units());
+
+        // the heaviest queue has nothing movable, so the next one donates
+        rebalance(&mut queues);
+        assert_eq!(queues[0].len(), 6);
+        assert!(queues[0].iter().all(TransactionState::is_pending));
+        assert!(
+            queues[1]
+                .total_compute_units()
+                .abs_diff(queues[2].total_compute_units())
+                <= per_transaction_cost
+        );
+        assert_eq!(queues[1].len() + queues[2].len(), 4);
+        assert!(!queues[2].is_empty());
+    }
+
+    #[test]
+    fn test_fee_weighted_median_age() {
+        let mut queue = TransactionStateQueue::default();
+        assert_eq!(queue.fee_weighted_median_age(100), 0);
+
+        // (received slot, compute unit price)
+        for (received_slot, priority) in [(99, 1), (98, 1), (97, 1), (91, 10)] {
+            queue.push(create_transaction_state(priority), received_slot);
+        }
+        // ages sorted: 1 (w=1), 2 (w=1), 3 (w=1), 9 (w=10); total weight 13.
+        // The cumulative weight first reaches half of the total at age 9,
+        // while the unweighted median would be between 2 and 3.
+        assert_eq!(queue.fee_weighted_median_age(100), 9);
+
+        // a heavier recent transaction pulls the median back down
+        queue.push(create_transaction_state(20), 100);
+        assert_eq!(queue.fee_weighted_median_age(100), 0);
+        queue.push(create_transaction_state(20), 95);
+        // ages sorted: 0 (20), 1 (1), 2 (1), 3 (1), 5 (20), 9 (10); total 53
+        assert_eq!(queue.fee_weighted_median_age(100), 5);
+
+        // zero-fee transactions carry no weight
+        let mut queue = TransactionStateQueue::default();
+        queue.push(create_transaction_state(0), 0);
+        assert_eq!(queue.fee_weighted_median_age(100), 0);
+    }
+}